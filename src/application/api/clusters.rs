@@ -0,0 +1,118 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+
+use crate::domain::errors::DomainError;
+use crate::domain::model::{ClusterDto, ClusterRegistry};
+
+/// Prefixes of the cluster routes: without cluster segment, they target the `cluster` query parameter or the default cluster
+pub const ROUTE_PREFIXES: [&str; 2] = ["/api", "/api/clusters/{cluster}"];
+
+#[derive(Deserialize)]
+struct ClusterParams {
+  cluster: Option<String>,
+}
+
+/// Name of the resource in the path of the cluster routes
+#[derive(Deserialize)]
+pub struct ResourcePath {
+  pub name: String,
+}
+
+/// Services of each cluster, picked from the cluster targeted by the requests
+pub struct Clusters<T> {
+  default_cluster: String,
+  services: HashMap<String, T>,
+}
+
+impl<T> Clusters<T> {
+  pub fn new(default_cluster: &str) -> Self {
+    Self {
+      default_cluster: default_cluster.to_string(),
+      services: HashMap::new(),
+    }
+  }
+
+  pub fn insert(&mut self, cluster: &str, service: T) {
+    self.services.insert(cluster.to_string(), service);
+  }
+
+  /// Id of the cluster targeted by the request: the `{cluster}` segment of its path, its `cluster` query parameter,
+  /// or the default cluster
+  pub fn cluster_id(&self, request: &HttpRequest) -> Result<String, DomainError> {
+    let cluster = match request.match_info().get("cluster") {
+      Some(cluster) => Some(cluster.to_string()),
+      None => web::Query::<ClusterParams>::from_query(request.query_string())
+        .ok()
+        .and_then(|params| params.into_inner().cluster),
+    }
+    .unwrap_or_else(|| self.default_cluster.clone());
+    self.get(&cluster)?;
+    Ok(cluster)
+  }
+
+  pub fn get(&self, cluster: &str) -> Result<&T, DomainError> {
+    self
+      .services
+      .get(cluster)
+      .ok_or_else(|| DomainError::NotFound(format!("Cluster {}", cluster)))
+  }
+
+  /// Service of the cluster targeted by the request
+  pub fn for_request(&self, request: &HttpRequest) -> Result<&T, DomainError> {
+    self.get(&self.cluster_id(request)?)
+  }
+}
+
+pub async fn list(registry: web::Data<ClusterRegistry>) -> Result<HttpResponse, DomainError> {
+  let clusters = registry
+    .clusters
+    .iter()
+    .map(|cluster| ClusterDto {
+      id: cluster.id.clone(),
+      repository: cluster.gitops.repository_path.clone(),
+      branch: cluster.gitops.branch.clone(),
+      default: cluster.id == registry.default_cluster,
+    })
+    .collect::<Vec<_>>();
+  Ok(HttpResponse::Ok().json(clusters))
+}
+
+pub fn routes(config: &mut web::ServiceConfig) {
+  config.route("/api/clusters", web::get().to(list));
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::test::TestRequest;
+
+  use crate::application::api::clusters::Clusters;
+  use crate::domain::errors::DomainError;
+
+  fn clusters() -> Clusters<&'static str> {
+    let mut clusters = Clusters::new("staging");
+    clusters.insert("staging", "staging service");
+    clusters.insert("production", "production service");
+    clusters
+  }
+
+  #[test]
+  fn target_the_cluster_of_the_request() {
+    let clusters = clusters();
+
+    let request = TestRequest::default().param("cluster", "production").to_http_request();
+    assert_eq!(clusters.for_request(&request).unwrap(), &"production service");
+
+    let request = TestRequest::with_uri("/api/nodegroups?cluster=production").to_http_request();
+    assert_eq!(clusters.for_request(&request).unwrap(), &"production service");
+
+    let request = TestRequest::with_uri("/api/nodegroups").to_http_request();
+    assert_eq!(clusters.cluster_id(&request).unwrap(), "staging");
+  }
+
+  #[test]
+  fn reject_the_unknown_clusters() {
+    let request = TestRequest::with_uri("/api/nodegroups?cluster=unknown").to_http_request();
+    assert!(matches!(clusters().for_request(&request), Err(DomainError::NotFound(_))));
+  }
+}
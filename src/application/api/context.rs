@@ -0,0 +1,36 @@
+use actix_web::HttpRequest;
+
+use crate::domain::model::RequestContext;
+
+/// Headers set by the authenticating proxies (oauth2-proxy and similar) with the user name
+const USER_HEADERS: [&str; 3] = ["X-Forwarded-User", "X-Auth-Request-User", "X-Remote-User"];
+
+/// Builds the context of the request from the HTTP headers
+pub fn request_context(request: &HttpRequest) -> RequestContext {
+  let user = USER_HEADERS
+    .iter()
+    .filter_map(|header| request.headers().get(*header))
+    .filter_map(|value| value.to_str().ok())
+    .map(str::trim)
+    .find(|user| !user.is_empty())
+    .map(String::from);
+
+  RequestContext { user, progress: None }
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::test::TestRequest;
+
+  use crate::application::api::context::request_context;
+
+  #[test]
+  fn read_the_user_from_the_proxy_headers() {
+    let request = TestRequest::default()
+      .insert_header(("X-Auth-Request-User", "jane.doe"))
+      .to_http_request();
+
+    assert_eq!(request_context(&request).user.as_deref(), Some("jane.doe"));
+    assert_eq!(request_context(&TestRequest::default().to_http_request()).user, None);
+  }
+}
@@ -0,0 +1,99 @@
+use actix_web::{error::JsonPayloadError, http::StatusCode, HttpRequest, HttpResponse, ResponseError};
+use serde_derive::Serialize;
+
+use crate::domain::errors::{DomainError, FieldError};
+
+const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 7807 problem details body
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProblemDetails {
+  #[serde(rename = "type")]
+  pub problem_type: String,
+  pub title: String,
+  pub status: u16,
+  pub detail: String,
+  pub code: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub errors: Option<Vec<FieldError>>,
+}
+
+impl From<&DomainError> for ProblemDetails {
+  fn from(error: &DomainError) -> Self {
+    let status = error.status_code();
+    let errors = match error {
+      DomainError::Validation(field_errors) => Some(field_errors.clone()),
+      _ => None,
+    };
+    Self {
+      problem_type: format!("/problems/{}", error.code().to_lowercase().replace('_', "-")),
+      title: status.canonical_reason().unwrap_or("Error").to_string(),
+      status: status.as_u16(),
+      detail: error.to_string(),
+      code: error.code().to_string(),
+      errors,
+    }
+  }
+}
+
+impl ResponseError for DomainError {
+  fn status_code(&self) -> StatusCode {
+    match self {
+      DomainError::NotFound(_) => StatusCode::NOT_FOUND,
+      DomainError::BadRequest(_) => StatusCode::BAD_REQUEST,
+      DomainError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+      DomainError::Conflict(_) => StatusCode::CONFLICT,
+      DomainError::Git(_) => StatusCode::BAD_GATEWAY,
+      DomainError::Kubeseal(_) | DomainError::Store(_) | DomainError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+  }
+
+  fn error_response(&self) -> HttpResponse {
+    HttpResponse::build(self.status_code())
+      .content_type(PROBLEM_JSON)
+      .json(ProblemDetails::from(self))
+  }
+}
+
+/// Turns the errors deserializing JSON payloads into problem+json responses
+pub fn json_error_handler(error: JsonPayloadError, _request: &HttpRequest) -> actix_web::Error {
+  DomainError::BadRequest(error.to_string()).into()
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{http::StatusCode, ResponseError};
+
+  use crate::application::api::errors::ProblemDetails;
+  use crate::domain::errors::{ConflictError, DomainError, FieldError};
+
+  #[test]
+  fn validation_errors_are_unprocessable_entities() {
+    let error = DomainError::Validation(vec![FieldError::new("name", "must be a valid DNS-1123 label")]);
+
+    let problem = ProblemDetails::from(&error);
+
+    assert_eq!(error.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(problem.status, 422);
+    assert_eq!(problem.code, "VALIDATION_FAILED");
+    assert_eq!(problem.problem_type, "/problems/validation-failed");
+    assert_eq!(problem.errors.unwrap()[0].field, "name");
+  }
+
+  #[test]
+  fn error_response_uses_problem_json() {
+    let response = DomainError::NotFound("NodeGroup test".to_string()).error_response();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+  }
+
+  #[test]
+  fn git_conflicts_are_conflicts() {
+    let conflict = DomainError::from_git(ConflictError("The changes conflict".to_string()).into());
+    let failure = DomainError::from_git(anyhow::anyhow!("Connection refused"));
+
+    assert_eq!(conflict.status_code(), StatusCode::CONFLICT);
+    assert_eq!(failure.status_code(), StatusCode::BAD_GATEWAY);
+  }
+}
@@ -1,12 +1,11 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 
+use crate::domain::errors::DomainError;
 use crate::domain::ports::incoming::InstanceTypesService;
 
-pub async fn list<S: InstanceTypesService>(service: web::Data<S>) -> impl Responder {
-  match service.list() {
-    Ok(list) => HttpResponse::Ok().json(list),
-    Err(e) => HttpResponse::InternalServerError().json(e.to_string()),
-  }
+pub async fn list<S: InstanceTypesService>(service: web::Data<S>) -> Result<HttpResponse, DomainError> {
+  let list = service.list()?;
+  Ok(HttpResponse::Ok().json(list))
 }
 
 pub fn routes<S: InstanceTypesService + 'static>(config: &mut web::ServiceConfig) {
@@ -0,0 +1,21 @@
+use actix_web::{http::header, web, HttpResponse};
+
+use crate::domain::errors::DomainError;
+use crate::domain::model::JobDto;
+use crate::domain::ports::incoming::JobService;
+
+pub async fn get<J: JobService>(id: web::Path<String>, service: web::Data<J>) -> Result<HttpResponse, DomainError> {
+  let job = service.get(&id)?;
+  Ok(HttpResponse::Ok().json(job))
+}
+
+/// Response of the requests run as a job, pointing to the job status
+pub fn accepted(job: JobDto) -> HttpResponse {
+  HttpResponse::Accepted()
+    .insert_header((header::LOCATION, format!("/api/jobs/{}", job.id)))
+    .json(job)
+}
+
+pub fn routes<J: JobService + 'static>(config: &mut web::ServiceConfig) {
+  config.route("/api/jobs/{id}", web::get().to(get::<J>));
+}
@@ -1,4 +1,8 @@
+pub mod clusters;
+pub mod context;
+pub mod errors;
 pub mod instance_types;
+pub mod jobs;
 pub mod nodegroups;
 pub mod probes;
 pub mod secrets;
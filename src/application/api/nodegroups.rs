@@ -1,31 +1,146 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_derive::Deserialize;
 
-use crate::domain::model::{NodeGroupDto, NodegroupRequestDto, ResponseStatusDto};
-use crate::domain::ports::incoming::NodegroupService;
+use crate::application::api::clusters::{Clusters, ResourcePath, ROUTE_PREFIXES};
+use crate::application::api::context::request_context;
+use crate::application::api::jobs::accepted;
+use crate::domain::errors::DomainError;
+use crate::domain::model::{NodeGroupDto, NodegroupRequestDto, NodegroupUpdateDto};
+use crate::domain::ports::incoming::{ChangeRequestTracker, JobService, NodegroupService};
 
-pub async fn list<S: NodegroupService<NodeGroupDto>>(service: web::Data<S>) -> impl Responder {
-  HttpResponse::Ok().json(service.list())
+pub async fn list<S: NodegroupService<NodeGroupDto>>(
+  http_request: HttpRequest,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  Ok(HttpResponse::Ok().json(services.for_request(&http_request)?.list()))
 }
 
-pub async fn get<S: NodegroupService<NodeGroupDto>>(name: web::Path<String>, service: web::Data<S>) -> impl Responder {
-  if let Some(dto) = service.get(&name) {
-    return HttpResponse::Ok().json(dto);
-  }
-  HttpResponse::NotFound().finish()
+/// Returns the nodegroup with its last change request. A nodegroup still to be created is returned with its change request only.
+pub async fn get<S: NodegroupService<NodeGroupDto>, C: ChangeRequestTracker>(
+  http_request: HttpRequest,
+  path: web::Path<ResourcePath>,
+  services: web::Data<Clusters<S>>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError> {
+  let name = &path.name;
+  let change_request = trackers.for_request(&http_request)?.status("nodegroup", name, None);
+  let nodegroup = match (services.for_request(&http_request)?.get(name), change_request) {
+    (Some(nodegroup), change_request) => NodeGroupDto {
+      change_request,
+      ..nodegroup
+    },
+    (None, Some(change_request)) if change_request.action != "delete" => NodeGroupDto {
+      name: name.to_string(),
+      change_request: Some(change_request),
+      ..Default::default()
+    },
+    _ => return Err(DomainError::NotFound(format!("NodeGroup {}", name))),
+  };
+  Ok(HttpResponse::Ok().json(nodegroup))
 }
 
-pub async fn create<S: NodegroupService<NodeGroupDto>>(request: web::Json<NodegroupRequestDto>, service: web::Data<S>) -> impl Responder {
-  match service.create(&request.to_owned()) {
-    Ok(_) =>HttpResponse::Ok().json(ResponseStatusDto{status: String::from("ok")}),
-    Err(_) => {
-      HttpResponse::InternalServerError().finish() //To be implemented
-    }
-  }
+/// Queues the creation of the nodegroup. Invalid requests are rejected right away, before being queued.
+pub async fn create<S, J, C>(
+  http_request: HttpRequest,
+  request: web::Json<NodegroupRequestDto>,
+  services: web::Data<Clusters<S>>,
+  jobs: web::Data<J>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError>
+where
+  S: NodegroupService<NodeGroupDto> + Sync + 'static,
+  J: JobService,
+  C: ChangeRequestTracker + Send + Sync + 'static,
+{
+  let request = request.into_inner();
+  let cluster = services.cluster_id(&http_request)?;
+  services.get(&cluster)?.render(&request)?;
+  let context = request_context(&http_request);
+  let name = request.name.clone();
+  let job = jobs.submit("nodegroup", &name, move |progress| async move {
+    let change_request = services.get(&cluster)?.create(&request, &context.with_progress(progress)).await?;
+    trackers
+      .get(&cluster)?
+      .track("nodegroup", &request.name, None, "create", &change_request)
+      .await;
+    Ok(change_request)
+  })?;
+  Ok(accepted(job))
+}
+
+pub async fn render<S: NodegroupService<NodeGroupDto>>(
+  http_request: HttpRequest,
+  request: web::Json<NodegroupRequestDto>,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  let manifest = services.for_request(&http_request)?.render(&request)?;
+  Ok(HttpResponse::Ok().content_type("application/yaml").body(manifest))
+}
+
+pub async fn diff<S: NodegroupService<NodeGroupDto>>(
+  http_request: HttpRequest,
+  request: web::Json<NodegroupRequestDto>,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  let diff = services.for_request(&http_request)?.diff(&request)?;
+  Ok(HttpResponse::Ok().json(diff))
 }
 
-pub fn routes<S: NodegroupService<NodeGroupDto> + 'static>(config: &mut web::ServiceConfig) {
-  config.route("/api/nodegroups", web::post().to(create::<S>));
-  config.route("/api/nodegroups", web::get().to(list::<S>));
-  config.route("/api/nodegroups/{name}", web::get().to(get::<S>));
+pub async fn update<S: NodegroupService<NodeGroupDto>, C: ChangeRequestTracker>(
+  http_request: HttpRequest,
+  path: web::Path<ResourcePath>,
+  request: web::Json<NodegroupUpdateDto>,
+  services: web::Data<Clusters<S>>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError> {
+  let change_request = services
+    .for_request(&http_request)?
+    .update(&path.name, &request, &request_context(&http_request))
+    .await?;
+  trackers
+    .for_request(&http_request)?
+    .track("nodegroup", &path.name, None, "update", &change_request)
+    .await;
+  Ok(HttpResponse::Ok().json(change_request))
 }
 
+#[derive(Deserialize)]
+pub struct DeleteParams {
+  #[serde(default)]
+  skip_pull_request: bool,
+}
+
+pub async fn delete<S: NodegroupService<NodeGroupDto>, C: ChangeRequestTracker>(
+  http_request: HttpRequest,
+  path: web::Path<ResourcePath>,
+  params: web::Query<DeleteParams>,
+  services: web::Data<Clusters<S>>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError> {
+  let change_request = services
+    .for_request(&http_request)?
+    .delete(&path.name, params.skip_pull_request, &request_context(&http_request))
+    .await?;
+  trackers
+    .for_request(&http_request)?
+    .track("nodegroup", &path.name, None, "delete", &change_request)
+    .await;
+  Ok(HttpResponse::Ok().json(change_request))
+}
+
+pub fn routes<S, J, C>(config: &mut web::ServiceConfig)
+where
+  S: NodegroupService<NodeGroupDto> + Sync + 'static,
+  J: JobService + 'static,
+  C: ChangeRequestTracker + Send + Sync + 'static,
+{
+  for prefix in ROUTE_PREFIXES {
+    config.route(&format!("{}/nodegroups", prefix), web::post().to(create::<S, J, C>));
+    config.route(&format!("{}/nodegroups", prefix), web::get().to(list::<S>));
+    config.route(&format!("{}/nodegroups/render", prefix), web::post().to(render::<S>));
+    config.route(&format!("{}/nodegroups/diff", prefix), web::post().to(diff::<S>));
+    config.route(&format!("{}/nodegroups/{{name}}", prefix), web::get().to(get::<S, C>));
+    config.route(&format!("{}/nodegroups/{{name}}", prefix), web::put().to(update::<S, C>));
+    config.route(&format!("{}/nodegroups/{{name}}", prefix), web::delete().to(delete::<S, C>));
+  }
+}
@@ -1,38 +1,108 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse};
 
-use crate::domain::model::SecretRequestDto;
-use crate::domain::ports::incoming::SecretService;
+use crate::application::api::clusters::{Clusters, ResourcePath, ROUTE_PREFIXES};
+use crate::application::api::context::request_context;
+use crate::application::api::jobs::accepted;
+use crate::domain::errors::DomainError;
+use crate::domain::model::{SecretRequestDto, SecretUpdateDto};
+use crate::domain::ports::incoming::{ChangeRequestTracker, JobService, SecretService};
 
-pub async fn list<S: SecretService>(repository: web::Data<S>) -> impl Responder {
-  HttpResponse::Ok().json(repository.list())
+pub async fn list<S: SecretService>(http_request: HttpRequest, services: web::Data<Clusters<S>>) -> Result<HttpResponse, DomainError> {
+  Ok(HttpResponse::Ok().json(services.for_request(&http_request)?.list()))
 }
 
-pub async fn get<S: SecretService>(name: web::Path<String>, repository: web::Data<S>) -> impl Responder {
-  if let Some(dto) = repository.get(&name) {
-    return HttpResponse::Ok().json(dto);
-  }
-  HttpResponse::NotFound().finish()
+pub async fn get<S: SecretService>(
+  http_request: HttpRequest,
+  path: web::Path<ResourcePath>,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  services
+    .for_request(&http_request)?
+    .get(&path.name)
+    .map(|dto| HttpResponse::Ok().json(dto))
+    .ok_or_else(|| DomainError::NotFound(format!("Secret {}", path.name)))
 }
 
-pub async fn create<S: SecretService>(request: web::Json<SecretRequestDto>, service: web::Data<S>) -> impl Responder {
-  match service.create(&request.to_owned()) {
-    Ok(_) => HttpResponse::Ok(),
-    Err(_) => {
-      HttpResponse::InternalServerError() //To be implemented
-    }
-  }
+/// Queues the creation of the sealed secret. Invalid requests are rejected right away, before being queued.
+pub async fn create<S, J, C>(
+  http_request: HttpRequest,
+  request: web::Json<SecretRequestDto>,
+  services: web::Data<Clusters<S>>,
+  jobs: web::Data<J>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError>
+where
+  S: SecretService + Send + Sync + 'static,
+  J: JobService,
+  C: ChangeRequestTracker + Send + Sync + 'static,
+{
+  let request = request.into_inner();
+  let cluster = services.cluster_id(&http_request)?;
+  services.get(&cluster)?.validate(&request)?;
+  let context = request_context(&http_request);
+  let name = request.name.clone();
+  let job = jobs.submit("secret", &name, move |progress| async move {
+    let service = services.get(&cluster)?;
+    let change_request = service.create(&request, &context.with_progress(progress)).await?;
+    let namespace = service.namespace(request.namespace.as_deref());
+    trackers
+      .get(&cluster)?
+      .track("secret", &request.name, Some(&namespace), "create", &change_request)
+      .await;
+    Ok(change_request)
+  })?;
+  Ok(accepted(job))
 }
 
-pub async fn render<S: SecretService>(request: web::Json<SecretRequestDto>, service: web::Data<S>) -> impl Responder {
-  match service.render(&request.to_owned()) {
-    Ok(rendered_response) => HttpResponse::Ok().content_type("application/yaml").body(rendered_response),
-    Err(error) => HttpResponse::InternalServerError().json(format!("Error rendering YAML for a SealedSecret: {}", error.to_string())),
-  }
+/// Adds, rotates or removes keys of the sealed secret, leaving its other keys untouched
+pub async fn update<S: SecretService, C: ChangeRequestTracker>(
+  http_request: HttpRequest,
+  path: web::Path<ResourcePath>,
+  request: web::Json<SecretUpdateDto>,
+  services: web::Data<Clusters<S>>,
+  trackers: web::Data<Clusters<C>>,
+) -> Result<HttpResponse, DomainError> {
+  let service = services.for_request(&http_request)?;
+  let change_request = service.update(&path.name, &request, &request_context(&http_request)).await?;
+  let namespace = service.namespace(request.namespace.as_deref());
+  trackers
+    .for_request(&http_request)?
+    .track("secret", &path.name, Some(&namespace), "update", &change_request)
+    .await;
+  Ok(HttpResponse::Ok().json(change_request))
+}
+
+pub async fn render<S: SecretService>(
+  http_request: HttpRequest,
+  request: web::Json<SecretRequestDto>,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  let rendered_response = services.for_request(&http_request)?.render(&request).await?;
+  Ok(HttpResponse::Ok().content_type("application/yaml").body(rendered_response))
+}
+
+/// Certificate of the sealed-secrets controller, to seal the secrets offline with `kubeseal --cert`
+pub async fn certificate<S: SecretService>(
+  http_request: HttpRequest,
+  services: web::Data<Clusters<S>>,
+) -> Result<HttpResponse, DomainError> {
+  let certificate = services.for_request(&http_request)?.certificate().await?;
+  Ok(HttpResponse::Ok().content_type("application/x-pem-file").body(certificate.pem))
 }
 
-pub fn routes<S: SecretService + 'static>(config: &mut web::ServiceConfig) {
-  config.route("/api/secrets", web::get().to(list::<S>));
-  config.route("/api/secrets/{name}", web::get().to(get::<S>));
-  config.route("/api/secrets", web::post().to(create::<S>));
-  config.route("/api/secrets/render", web::post().to(render::<S>));
+pub fn routes<S, J, C>(config: &mut web::ServiceConfig)
+where
+  S: SecretService + Send + Sync + 'static,
+  J: JobService + 'static,
+  C: ChangeRequestTracker + Send + Sync + 'static,
+{
+  for prefix in ROUTE_PREFIXES {
+    config.route(&format!("{}/secrets", prefix), web::get().to(list::<S>));
+    // Before the secrets by name, not to be taken for one
+    config.route(&format!("{}/secrets/certificate", prefix), web::get().to(certificate::<S>));
+    config.route(&format!("{}/secrets/{{name}}", prefix), web::get().to(get::<S>));
+    config.route(&format!("{}/secrets/{{name}}", prefix), web::patch().to(update::<S, C>));
+    config.route(&format!("{}/secrets", prefix), web::post().to(create::<S, J, C>));
+    config.route(&format!("{}/secrets/render", prefix), web::post().to(render::<S>));
+  }
 }
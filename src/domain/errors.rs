@@ -0,0 +1,75 @@
+use serde_derive::Serialize;
+use thiserror::Error;
+
+/// Error on a single field of a request
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+  pub field: String,
+  pub message: String,
+}
+
+impl FieldError {
+  pub fn new<F: Into<String>, M: Into<String>>(field: F, message: M) -> Self {
+    Self {
+      field: field.into(),
+      message: message.into(),
+    }
+  }
+}
+
+/// Error returned by the outgoing ports when a change can't be applied on top of the current state of the remote
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct ConflictError(pub String);
+
+/// Errors returned by the domain services
+#[derive(Error, Debug)]
+pub enum DomainError {
+  #[error("{0} not found")]
+  NotFound(String),
+
+  #[error("Malformed request: {0}")]
+  BadRequest(String),
+
+  #[error("Invalid request: {} field error(s)", .0.len())]
+  Validation(Vec<FieldError>),
+
+  #[error("Conflict: {0}")]
+  Conflict(String),
+
+  #[error("Error in the GitOps repository: {0}")]
+  Git(#[source] anyhow::Error),
+
+  #[error("Error sealing the secret: {0}")]
+  Kubeseal(#[source] anyhow::Error),
+
+  #[error("Error accessing the store: {0}")]
+  Store(#[source] anyhow::Error),
+
+  #[error("Internal error: {0}")]
+  Internal(#[source] anyhow::Error),
+}
+
+impl DomainError {
+  /// Wraps an error of the GitOps repository, keeping the conflicts apart from the other failures
+  pub fn from_git(error: anyhow::Error) -> Self {
+    match error.downcast::<ConflictError>() {
+      Ok(conflict) => DomainError::Conflict(conflict.0),
+      Err(error) => DomainError::Git(error),
+    }
+  }
+
+  /// Stable code identifying the kind of error, safe to be used by the clients
+  pub fn code(&self) -> &'static str {
+    match self {
+      DomainError::NotFound(_) => "NOT_FOUND",
+      DomainError::BadRequest(_) => "BAD_REQUEST",
+      DomainError::Validation(_) => "VALIDATION_FAILED",
+      DomainError::Conflict(_) => "CONFLICT",
+      DomainError::Git(_) => "GIT_FAILURE",
+      DomainError::Kubeseal(_) => "KUBESEAL_FAILURE",
+      DomainError::Store(_) => "STORE_FAILURE",
+      DomainError::Internal(_) => "INTERNAL_ERROR",
+    }
+  }
+}
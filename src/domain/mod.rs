@@ -1,3 +1,4 @@
+pub mod errors;
 pub mod model;
 pub mod ports;
 pub mod services;
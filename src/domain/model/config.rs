@@ -1,8 +1,233 @@
+use serde_derive::Serialize;
+use std::collections::HashSet;
+
+use crate::domain::model::{CertificateSource, SealingPolicy, SealingScope};
+use crate::utils::validators::dns::is_dns1123_label;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GitOpsConfig {
   pub repository_name: String,
   pub organization: String,
   pub branch: String,
+  /// Folder of the temporary worktrees, one per git operation
   pub destination_folder: String,
+  /// Folder keeping the bare mirror of the repository between operations
+  pub mirror_folder: String,
   pub repository_path: String,
+  pub repository_url: String,
+  pub username: Option<String>,
+  pub token: Option<String>,
+  pub author_name: String,
+  pub author_email: String,
+  pub provider: GitProvider,
+  pub api_url: String,
+  /// Maximum number of git operations (clone, commit, push) running at the same time
+  pub max_concurrent_operations: usize,
+}
+
+/// Git hosting service where the pull requests are opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GitProvider {
+  GitHub,
+  GitLab,
+  Gitea,
+}
+
+impl GitProvider {
+  /// Default REST API endpoint for the given host
+  pub fn api_url(&self, host: &str) -> String {
+    match self {
+      GitProvider::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+      GitProvider::GitHub => format!("https://{}/api/v3", host),
+      GitProvider::GitLab => format!("https://{}/api/v4", host),
+      GitProvider::Gitea => format!("https://{}/api/v1", host),
+    }
+  }
+}
+
+impl std::str::FromStr for GitProvider {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_lowercase().as_str() {
+      "github" => Ok(GitProvider::GitHub),
+      "gitlab" => Ok(GitProvider::GitLab),
+      "gitea" => Ok(GitProvider::Gitea),
+      _ => Err(format!("Unknown git provider: {}", value)),
+    }
+  }
+}
+
+/// Catalog of the GitOps repository where the manifests are written by default
+pub const DEFAULT_CATALOG_PATH: &str = "infrastructure/_catalog/templates";
+
+/// Where the manifests of each kind are written in the GitOps repository of a cluster.
+/// The paths are Handlebars templates, relative to the root of the repository, getting the `name` of the resource
+/// and the `cluster` id, like `clusters/{{cluster}}/nodegroups/{{name}}.yaml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestLayout {
+  pub cluster: String,
+  pub nodegroup: String,
+  pub secret: String,
+}
+
+impl ManifestLayout {
+  /// Layout writing every manifest to the catalog folder, as `nodegroup-{name}.yaml` or `sealed-secret-{name}.yaml`
+  pub fn in_catalog(cluster: &str, catalog_path: &str) -> Self {
+    Self {
+      cluster: cluster.to_string(),
+      nodegroup: format!("{}/nodegroup-{{{{name}}}}.yaml", catalog_path),
+      secret: format!("{}/sealed-secret-{{{{name}}}}.yaml", catalog_path),
+    }
+  }
+}
+
+/// Cluster managed through its own GitOps repository
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClusterConfig {
+  /// Identifier of the cluster in the API routes
+  pub id: String,
+  pub gitops: GitOpsConfig,
+  pub layout: ManifestLayout,
+  /// Context of the kubeconfig used to reach the cluster. The default context when missing
+  pub kube_context: Option<String>,
+  /// Namespace of the secrets in the cluster
+  pub namespace: String,
+  /// Where the certificate of the sealed-secrets controller of the cluster is loaded from
+  pub sealing_certificate: CertificateSource,
+  /// Scope of the sealed secrets
+  pub sealing_scope: SealingScope,
+  /// Namespaces the requests can seal secrets for, besides `namespace`
+  pub sealing_namespaces: Vec<String>,
+}
+
+impl ClusterConfig {
+  /// Sealing defaults of the cluster, allowing its namespace and the extra sealing namespaces
+  pub fn sealing_policy(&self) -> SealingPolicy {
+    let mut allowed_namespaces = vec![self.namespace.clone()];
+    for namespace in &self.sealing_namespaces {
+      if !allowed_namespaces.contains(namespace) {
+        allowed_namespaces.push(namespace.clone());
+      }
+    }
+    SealingPolicy {
+      namespace: self.namespace.clone(),
+      scope: self.sealing_scope,
+      allowed_namespaces,
+    }
+  }
+}
+
+/// Clusters known to the API, with the one used when a request doesn't name any
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClusterRegistry {
+  pub default_cluster: String,
+  pub clusters: Vec<ClusterConfig>,
+}
+
+/// Cluster exposed by the API, without the credentials of its repository
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClusterDto {
+  pub id: String,
+  /// `{organization}/{repository}` of the GitOps repository
+  pub repository: String,
+  pub branch: String,
+  /// Whether the routes without cluster target this cluster
+  pub default: bool,
+}
+
+impl ClusterRegistry {
+  /// Checks that the cluster ids are unique DNS-1123 labels, as they appear in the routes and the folder names,
+  /// that the sealing namespaces are valid, and that the default cluster is one of them
+  pub fn new(default_cluster: Option<String>, clusters: Vec<ClusterConfig>) -> Result<Self, String> {
+    let mut ids = HashSet::new();
+    for cluster in &clusters {
+      if !is_dns1123_label(&cluster.id) {
+        return Err(format!("The cluster id {} must be a DNS-1123 label", cluster.id));
+      }
+      if !ids.insert(cluster.id.as_str()) {
+        return Err(format!("The cluster {} is configured twice", cluster.id));
+      }
+      if let Some(namespace) = cluster.sealing_namespaces.iter().find(|namespace| !is_dns1123_label(namespace)) {
+        return Err(format!(
+          "The sealing namespace {} of the cluster {} must be a DNS-1123 label",
+          namespace, cluster.id
+        ));
+      }
+    }
+    let default_cluster = match default_cluster {
+      Some(id) if ids.contains(id.as_str()) => id,
+      Some(id) => return Err(format!("The default cluster {} isn't configured", id)),
+      None => clusters.first().map(|cluster| cluster.id.clone()).ok_or("No cluster configured")?,
+    };
+    Ok(Self { default_cluster, clusters })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::domain::model::{CertificateSource, ClusterConfig, ClusterRegistry, GitOpsConfig, GitProvider, ManifestLayout, SealingScope};
+
+  fn cluster(id: &str) -> ClusterConfig {
+    ClusterConfig {
+      id: id.to_string(),
+      gitops: GitOpsConfig {
+        repository_name: format!("gitops-{}", id),
+        organization: "acme".to_string(),
+        branch: "main".to_string(),
+        destination_folder: format!("/tmp/workspaces/{}", id),
+        mirror_folder: format!("/tmp/stores/gitops/{}", id),
+        repository_path: format!("acme/gitops-{}", id),
+        repository_url: format!("https://github.com/acme/gitops-{}.git", id),
+        username: None,
+        token: None,
+        author_name: "unicron-backend-api".to_string(),
+        author_email: "unicron-backend-api@users.noreply.github.com".to_string(),
+        provider: GitProvider::GitHub,
+        api_url: "https://api.github.com".to_string(),
+        max_concurrent_operations: 4,
+      },
+      layout: ManifestLayout::in_catalog(id, "infrastructure/_catalog/templates"),
+      kube_context: Some(id.to_string()),
+      namespace: "default".to_string(),
+      sealing_certificate: CertificateSource::File("/etc/sealed-secrets/cert.pem".to_string()),
+      sealing_scope: SealingScope::Strict,
+      sealing_namespaces: vec![],
+    }
+  }
+
+  #[test]
+  fn default_to_the_first_cluster() {
+    let registry = ClusterRegistry::new(None, vec![cluster("staging"), cluster("production")]).unwrap();
+    assert_eq!(registry.default_cluster, "staging");
+
+    let registry = ClusterRegistry::new(Some("production".to_string()), vec![cluster("staging"), cluster("production")]).unwrap();
+    assert_eq!(registry.default_cluster, "production");
+  }
+
+  #[test]
+  fn allow_the_cluster_namespace_and_the_sealing_namespaces() {
+    let cluster = ClusterConfig {
+      sealing_namespaces: vec!["payments".to_string(), "default".to_string()],
+      ..cluster("staging")
+    };
+
+    let policy = cluster.sealing_policy();
+
+    assert_eq!(policy.namespace, "default");
+    assert_eq!(policy.allowed_namespaces, vec!["default".to_string(), "payments".to_string()]);
+  }
+
+  #[test]
+  fn reject_the_invalid_registries() {
+    assert!(ClusterRegistry::new(None, vec![]).is_err());
+    assert!(ClusterRegistry::new(None, vec![cluster("staging"), cluster("staging")]).is_err());
+    assert!(ClusterRegistry::new(None, vec![cluster("../staging")]).is_err());
+    let invalid_namespace = ClusterConfig {
+      sealing_namespaces: vec!["Payments".to_string()],
+      ..cluster("staging")
+    };
+    assert!(ClusterRegistry::new(None, vec![invalid_namespace]).is_err());
+    assert!(ClusterRegistry::new(Some("production".to_string()), vec![cluster("staging")]).is_err());
+  }
 }
@@ -0,0 +1,133 @@
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::domain::model::ChangeRequestDto;
+use crate::utils::time::now;
+
+/// Lifecycle of a job
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+  Queued,
+  Running,
+  Succeeded,
+  Failed,
+}
+
+/// Steps reached by a GitOps change while its job runs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStep {
+  Queued,
+  Cloned,
+  Rendered,
+  Pushed,
+  PullRequestOpened,
+  Failed,
+}
+
+/// Step reached by a job, with the time (seconds since the epoch) it was reached
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct JobStepDto {
+  pub step: JobStep,
+  pub timestamp: u64,
+}
+
+/// Long-running GitOps operation, run in the background and queried by its id
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct JobDto {
+  pub id: String,
+  /// Kind of the resource changed (`nodegroup`, `secret`...)
+  pub kind: String,
+  /// Name of the resource changed
+  pub name: String,
+  pub status: JobStatus,
+  pub steps: Vec<JobStepDto>,
+  /// Change pushed to the GitOps repository, once the job succeeds
+  pub result: Option<ChangeRequestDto>,
+  /// Reason of the failure, once the job fails
+  pub error: Option<String>,
+  pub created_at: u64,
+  pub updated_at: u64,
+}
+
+impl JobDto {
+  pub fn new(id: String, kind: &str, name: &str) -> Self {
+    let now = now();
+    Self {
+      id,
+      kind: kind.to_string(),
+      name: name.to_string(),
+      status: JobStatus::Queued,
+      steps: vec![JobStepDto {
+        step: JobStep::Queued,
+        timestamp: now,
+      }],
+      result: None,
+      error: None,
+      created_at: now,
+      updated_at: now,
+    }
+  }
+
+  pub fn is_finished(&self) -> bool {
+    matches!(self.status, JobStatus::Succeeded | JobStatus::Failed)
+  }
+
+  pub fn start(&mut self) {
+    self.status = JobStatus::Running;
+    self.updated_at = now();
+  }
+
+  pub fn advance(&mut self, step: JobStep) {
+    let timestamp = now();
+    self.steps.push(JobStepDto { step, timestamp });
+    self.updated_at = timestamp;
+  }
+
+  pub fn succeed(&mut self, result: ChangeRequestDto) {
+    self.status = JobStatus::Succeeded;
+    self.result = Some(result);
+    self.updated_at = now();
+  }
+
+  pub fn fail<E: ToString>(&mut self, error: E) {
+    self.advance(JobStep::Failed);
+    self.status = JobStatus::Failed;
+    self.error = Some(error.to_string());
+  }
+}
+
+/// Reports the steps reached by a running job
+#[derive(Clone)]
+pub struct JobProgress {
+  job_id: String,
+  listener: Arc<dyn Fn(JobStep) + Send + Sync>,
+}
+
+impl JobProgress {
+  pub fn new<L: Fn(JobStep) + Send + Sync + 'static>(job_id: &str, listener: L) -> Self {
+    Self {
+      job_id: job_id.to_string(),
+      listener: Arc::new(listener),
+    }
+  }
+
+  pub fn report(&self, step: JobStep) {
+    (self.listener)(step)
+  }
+}
+
+impl std::fmt::Debug for JobProgress {
+  fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    formatter.debug_struct("JobProgress").field("job_id", &self.job_id).finish()
+  }
+}
+
+impl PartialEq for JobProgress {
+  fn eq(&self, other: &Self) -> bool {
+    self.job_id == other.job_id
+  }
+}
+
+impl Eq for JobProgress {}
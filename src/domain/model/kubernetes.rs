@@ -1,32 +1,166 @@
-use crate::domain::model::InstanceType;
+use crate::domain::model::{InstanceType, TrackedChangeDto};
+use crate::utils::memory::MemoryQuantity;
+use serde::{Deserialize as _, Deserializer};
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
-
-pub struct ResponseStatusDto {
-  pub status: String,
-}
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
 
 pub struct NodeGroupDto {
   pub name: String,
+  pub enabled: Option<bool>,
+  pub availability_zone_number: Option<usize>,
   pub instance_name: Option<String>,
+  pub alternate_instance_names: Vec<String>,
   pub min_size: Option<usize>,
   pub max_size: Option<usize>,
+  pub target_size: Option<usize>,
   pub ephemeral: Option<String>,
+  pub lifecycle: Option<String>,
+  pub spot_allocation_strategy: Option<String>,
+  pub labels: BTreeMap<String, String>,
+  pub taints: Vec<TaintDto>,
   pub state: Option<String>,
+  pub conditions: Vec<NodeGroupConditionDto>,
+  pub creation_timestamp: Option<String>,
+  pub last_update_time: Option<String>,
+  /// Version of the NodeGroup object in the cluster, changing on every update
+  pub resource_version: Option<String>,
   pub instance_type: Option<InstanceType>,
+  /// Last change request submitted through the API for the nodegroup, if any
+  pub change_request: Option<TrackedChangeDto>,
+}
+
+/// Kubernetes taint applied to every node of a nodegroup
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TaintDto {
+  pub key: String,
+  pub value: Option<String>,
+  pub effect: String,
+}
+
+/// Condition reported by the nodegroup controller in the status
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct NodeGroupConditionDto {
+  #[serde(rename = "type")]
+  pub condition_type: String,
+  pub status: String,
+  pub reason: Option<String>,
+  pub message: Option<String>,
+  pub last_transition_time: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct NodegroupRequestDto {
   pub name: String,
   pub default_instance_type: String,
-  pub alternate_instance_type: Option<String>,
-  pub min_size: Option<String>,
-  pub max_size: Option<String>,
-  pub target_size: Option<String>,
-  pub ephemeral: String,
+  /// Alternate instance types, in order of preference. A single one is also accepted as `alternate_instance_type`
+  #[serde(default, alias = "alternate_instance_type", deserialize_with = "one_or_many")]
+  pub alternate_instance_types: Vec<String>,
+  pub min_size: Option<usize>,
+  pub max_size: Option<usize>,
+  pub target_size: Option<usize>,
+  pub ephemeral: MemoryQuantity,
+  /// `ondemand` (default) or `spot`
+  pub lifecycle: Option<String>,
+  /// Allocation strategy of the spot instances. Defaults to `capacity-optimized`
+  pub spot_allocation_strategy: Option<String>,
+  /// Number of availability zones. Defaults to 1
+  pub availability_zone_number: Option<usize>,
+  #[serde(default)]
+  pub labels: BTreeMap<String, String>,
+  #[serde(default)]
+  pub taints: Vec<TaintDto>,
+  pub skip_pull_request: bool,
+}
+
+/// Changes to apply to an existing nodegroup. Missing fields keep their current value.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodegroupUpdateDto {
+  pub default_instance_type: Option<String>,
+  #[serde(default, alias = "alternate_instance_type", deserialize_with = "optional_one_or_many")]
+  pub alternate_instance_types: Option<Vec<String>>,
+  pub min_size: Option<usize>,
+  pub max_size: Option<usize>,
+  pub target_size: Option<usize>,
+  pub ephemeral: Option<MemoryQuantity>,
+  pub lifecycle: Option<String>,
+  pub spot_allocation_strategy: Option<String>,
+  pub availability_zone_number: Option<usize>,
+  pub labels: Option<BTreeMap<String, String>>,
+  pub taints: Option<Vec<TaintDto>>,
+  #[serde(default)]
   pub skip_pull_request: bool,
 }
+
+/// Field of a nodegroup request that differs from the live object in the cluster
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChangeDto {
+  pub field: String,
+  pub current: Option<serde_json::Value>,
+  pub proposed: serde_json::Value,
+}
+
+/// Differences between a nodegroup request and the live object in the cluster
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct NodegroupDiffDto {
+  pub name: String,
+  pub changes: Vec<FieldChangeDto>,
+  /// Unified diff between the current and the proposed manifests
+  pub unified_diff: String,
+}
+
+/// Single value or list of values
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+  One(String),
+  Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+  fn from(values: OneOrMany) -> Self {
+    match values {
+      OneOrMany::One(value) => vec![value],
+      OneOrMany::Many(values) => values,
+    }
+  }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+  Ok(Option::<OneOrMany>::deserialize(deserializer)?.map(Vec::from).unwrap_or_default())
+}
+
+fn optional_one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+  Ok(Option::<OneOrMany>::deserialize(deserializer)?.map(Vec::from))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::domain::model::{NodegroupRequestDto, NodegroupUpdateDto};
+
+  #[test]
+  fn accept_one_or_many_alternate_instance_types() {
+    let request = |alternates: &str| {
+      let request = format!(
+        r#"{{"name": "batch-pool", "default_instance_type": "m5.large", {}"ephemeral": "20Gi", "skip_pull_request": false}}"#,
+        alternates
+      );
+      serde_json::from_str::<NodegroupRequestDto>(&request)
+        .unwrap()
+        .alternate_instance_types
+    };
+
+    assert!(request("").is_empty());
+    assert_eq!(request(r#""alternate_instance_type": "m5a.large", "#), vec!["m5a.large"]);
+    assert_eq!(
+      request(r#""alternate_instance_types": ["m5a.large", "m6a.large"], "#),
+      vec!["m5a.large", "m6a.large"]
+    );
+
+    let update = serde_json::from_str::<NodegroupUpdateDto>(r#"{"max_size": 10}"#).unwrap();
+    assert_eq!(update.alternate_instance_types, None);
+    let update = serde_json::from_str::<NodegroupUpdateDto>(r#"{"alternate_instance_types": []}"#).unwrap();
+    assert_eq!(update.alternate_instance_types, Some(vec![]));
+  }
+}
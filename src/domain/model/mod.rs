@@ -1,9 +1,23 @@
 pub mod config;
 pub mod instance_type;
+pub mod jobs;
 pub mod kubernetes;
+pub mod request_context;
 pub mod secrets;
+pub mod version_control;
 
-pub use config::GitOpsConfig;
+pub use config::{ClusterConfig, ClusterDto, ClusterRegistry, GitOpsConfig, GitProvider, ManifestLayout, DEFAULT_CATALOG_PATH};
 pub use instance_type::{InstanceType, InstanceTypesList};
-pub use kubernetes::{NodeGroupDto, NodegroupRequestDto, ResponseStatusDto};
-pub use secrets::{SecretDto, SecretRequestDto};
+pub use jobs::{JobDto, JobProgress, JobStep};
+pub use kubernetes::{
+  FieldChangeDto, NodeGroupConditionDto, NodeGroupDto, NodegroupDiffDto, NodegroupRequestDto, NodegroupUpdateDto, TaintDto,
+};
+pub use request_context::RequestContext;
+pub use secrets::{
+  CertificateSource, SealedSecretDto, SealingCertificateDto, SealingPolicy, SealingScope, SealingTarget, SecretData, SecretDto,
+  SecretRequestDto, SecretType, SecretUpdateDto, SecretsSettings,
+};
+pub use version_control::{
+  ChangeRequestDto, ChangeRequestMessages, ChangeRequestMode, ChangeRequestState, CommitDto, NewPullRequest, PullRequestDto,
+  PullRequestState, TrackedChangeDto, Workspace,
+};
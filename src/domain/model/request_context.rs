@@ -0,0 +1,29 @@
+use serde_derive::Serialize;
+
+use crate::domain::model::{JobProgress, JobStep};
+
+/// Information about who or what issued a request, taken from the incoming HTTP request
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RequestContext {
+  /// User authenticated by the proxy in front of the API, if any
+  pub user: Option<String>,
+  /// Progress of the job running the request, when it runs in the background
+  #[serde(skip)]
+  pub progress: Option<JobProgress>,
+}
+
+impl RequestContext {
+  pub fn with_progress(self, progress: JobProgress) -> Self {
+    Self {
+      progress: Some(progress),
+      ..self
+    }
+  }
+
+  /// Reports a step of the request to its job, if any
+  pub fn report(&self, step: JobStep) {
+    if let Some(progress) = &self.progress {
+      progress.report(step);
+    }
+  }
+}
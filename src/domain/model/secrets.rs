@@ -1,5 +1,7 @@
 use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::model::ManifestLayout;
 
 #[derive(Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct SecretDto {
@@ -11,6 +13,286 @@ pub struct SecretDto {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct SecretRequestDto {
   pub name: String,
+  /// Type of the secret. `opaque` when missing
+  #[serde(rename = "type", default)]
+  pub secret_type: SecretType,
+  /// Text values of an `opaque` secret
+  #[serde(default)]
   pub literals: HashMap<String, String>,
+  /// Base64 encoded values of an `opaque` secret, like keystores or SSH keys
+  #[serde(default)]
+  pub files: HashMap<String, String>,
+  /// Credentials of a `docker-registry` secret
+  pub docker_registry: Option<DockerRegistryDto>,
+  /// Certificate and key of a `tls` secret
+  pub tls: Option<TlsDto>,
   pub skip_pull_request: bool,
+  /// Namespace of the unsealed secret. The namespace of the cluster when missing
+  pub namespace: Option<String>,
+  /// Scope of the sealed secret. The scope of the cluster when missing
+  pub scope: Option<SealingScope>,
+}
+
+/// Keys to add, rotate or remove in an existing sealed secret. The other keys keep their ciphertexts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecretUpdateDto {
+  /// Text values of the keys to add or rotate
+  #[serde(default)]
+  pub literals: HashMap<String, String>,
+  /// Base64 encoded values of the keys to add or rotate
+  #[serde(default)]
+  pub files: HashMap<String, String>,
+  /// Keys to remove
+  #[serde(default)]
+  pub remove: Vec<String>,
+  /// Namespace of the secret. The namespace of the cluster when missing
+  pub namespace: Option<String>,
+  #[serde(default)]
+  pub skip_pull_request: bool,
+}
+
+/// Sealed secret as found in the GitOps repository
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealedSecretDto {
+  pub name: String,
+  pub namespace: String,
+  pub scope: SealingScope,
+  /// Type of the unsealed secret
+  pub secret_type: String,
+  /// Keys of the encrypted data, sorted
+  pub keys: Vec<String>,
+}
+
+/// Types of secrets that can be requested, like the `kubectl create secret` sub-commands
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretType {
+  /// Arbitrary keys, from the literals and the files
+  #[default]
+  Opaque,
+  /// `.dockerconfigjson` key, from the credentials of a registry
+  DockerRegistry,
+  /// `tls.crt` and `tls.key` keys, from a certificate and its private key
+  Tls,
+}
+
+impl SecretType {
+  /// Type of the Kubernetes secret
+  pub fn kubernetes_type(&self) -> &'static str {
+    match self {
+      SecretType::Opaque => "Opaque",
+      SecretType::DockerRegistry => "kubernetes.io/dockerconfigjson",
+      SecretType::Tls => "kubernetes.io/tls",
+    }
+  }
+}
+
+impl std::fmt::Display for SecretType {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SecretType::Opaque => write!(f, "opaque"),
+      SecretType::DockerRegistry => write!(f, "docker-registry"),
+      SecretType::Tls => write!(f, "tls"),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DockerRegistryDto {
+  /// Registry host, like `ghcr.io` or `https://index.docker.io/v1/`
+  pub server: String,
+  pub username: String,
+  pub password: String,
+  pub email: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TlsDto {
+  /// PEM certificate, optionally followed by its chain
+  pub certificate: String,
+  /// PEM private key of the certificate
+  pub key: String,
+}
+
+/// Secret ready to be sealed: its Kubernetes type and the raw value of every key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretData {
+  pub name: String,
+  pub secret_type: String,
+  pub data: BTreeMap<String, Vec<u8>>,
+}
+
+/// Scope of a sealed secret: the secrets the controller accepts to unseal it into
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SealingScope {
+  /// Only the secret with the same name and namespace
+  #[default]
+  Strict,
+  /// Any secret of the same namespace
+  NamespaceWide,
+  /// Any secret of the cluster
+  ClusterWide,
+}
+
+impl std::str::FromStr for SealingScope {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    match value.to_lowercase().as_str() {
+      "strict" => Ok(SealingScope::Strict),
+      "namespace-wide" => Ok(SealingScope::NamespaceWide),
+      "cluster-wide" => Ok(SealingScope::ClusterWide),
+      _ => Err(format!("Unknown sealing scope: {}", value)),
+    }
+  }
+}
+
+/// Namespace of the unsealed secret and scope of the sealed one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealingTarget {
+  pub namespace: String,
+  pub scope: SealingScope,
+}
+
+/// Namespace and scope of the sealed secrets of a cluster, with the namespaces the requests can pick instead
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealingPolicy {
+  pub namespace: String,
+  pub scope: SealingScope,
+  /// Namespaces allowed in the requests, the default one included
+  pub allowed_namespaces: Vec<String>,
+}
+
+impl SealingPolicy {
+  /// Target of the request, defaulting to the namespace and scope of the cluster
+  pub fn target(&self, request: &SecretRequestDto) -> SealingTarget {
+    SealingTarget {
+      namespace: request.namespace.clone().unwrap_or_else(|| self.namespace.clone()),
+      scope: request.scope.unwrap_or(self.scope),
+    }
+  }
+}
+
+/// Settings of the secrets of a cluster: where their manifests are written and how they're sealed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretsSettings {
+  pub layout: ManifestLayout,
+  pub sealing_policy: SealingPolicy,
+}
+
+/// Where the public certificate of the sealed-secrets controller is loaded from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CertificateSource {
+  /// PEM file
+  File(String),
+  /// URL serving the PEM certificate
+  Url(String),
+  /// `/v1/cert.pem` endpoint of the controller service, reached through the proxy of the Kubernetes API
+  Service { namespace: String, name: String },
+  /// Latest active key secret of the controller, in its namespace
+  Secret { namespace: String },
+}
+
+impl Default for CertificateSource {
+  /// The service of a default sealed-secrets installation, like `kubeseal` does
+  fn default() -> Self {
+    CertificateSource::Service {
+      namespace: "kube-system".to_string(),
+      name: "sealed-secrets-controller".to_string(),
+    }
+  }
+}
+
+impl std::str::FromStr for CertificateSource {
+  type Err = String;
+
+  /// Parses `http(s)://{url}`, `service:{namespace}/{name}`, `secret:{namespace}` or the path of a PEM file,
+  /// optionally prefixed by `file:`
+  fn from_str(value: &str) -> Result<Self, Self::Err> {
+    if value.starts_with("http://") || value.starts_with("https://") {
+      return Ok(CertificateSource::Url(value.to_string()));
+    }
+    if let Some(service) = value.strip_prefix("service:") {
+      return match service.split_once('/') {
+        Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => Ok(CertificateSource::Service {
+          namespace: namespace.to_string(),
+          name: name.to_string(),
+        }),
+        _ => Err(format!(
+          "Invalid controller service, expected service:{{namespace}}/{{name}}: {}",
+          value
+        )),
+      };
+    }
+    if let Some(namespace) = value.strip_prefix("secret:") {
+      return match namespace.is_empty() {
+        true => Err(format!("Invalid controller secret, expected secret:{{namespace}}: {}", value)),
+        false => Ok(CertificateSource::Secret {
+          namespace: namespace.to_string(),
+        }),
+      };
+    }
+    match value.strip_prefix("file:").unwrap_or(value) {
+      "" => Err("Empty certificate path".to_string()),
+      path => Ok(CertificateSource::File(path.to_string())),
+    }
+  }
+}
+
+impl std::fmt::Display for CertificateSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CertificateSource::File(path) => write!(f, "file:{}", path),
+      CertificateSource::Url(url) => write!(f, "{}", url),
+      CertificateSource::Service { namespace, name } => write!(f, "service:{}/{}", namespace, name),
+      CertificateSource::Secret { namespace } => write!(f, "secret:{}", namespace),
+    }
+  }
+}
+
+/// Public certificate of the sealed-secrets controller, used to seal the secrets
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SealingCertificateDto {
+  /// PEM encoded certificate
+  pub pem: String,
+  /// Expiry of the certificate, in seconds since the epoch
+  pub not_after: i64,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::domain::model::CertificateSource;
+
+  #[test]
+  fn parse_the_certificate_sources() {
+    let sources = [
+      (
+        "/etc/sealed-secrets/cert.pem",
+        CertificateSource::File("/etc/sealed-secrets/cert.pem".to_string()),
+      ),
+      ("file:cert.pem", CertificateSource::File("cert.pem".to_string())),
+      (
+        "https://sealed-secrets.example.com/v1/cert.pem",
+        CertificateSource::Url("https://sealed-secrets.example.com/v1/cert.pem".to_string()),
+      ),
+      ("service:kube-system/sealed-secrets-controller", CertificateSource::default()),
+      (
+        "secret:sealed-secrets",
+        CertificateSource::Secret {
+          namespace: "sealed-secrets".to_string(),
+        },
+      ),
+    ];
+    for (value, source) in sources {
+      assert_eq!(value.parse::<CertificateSource>().as_ref(), Ok(&source));
+    }
+    for value in ["", "file:", "service:kube-system", "service:/controller", "secret:"] {
+      assert!(
+        value.parse::<CertificateSource>().is_err(),
+        "{} shouldn't be a certificate source",
+        value
+      );
+    }
+  }
 }
@@ -0,0 +1,150 @@
+use serde_derive::{Deserialize, Serialize};
+use std::any::Any;
+
+/// Pull request to be opened in the git provider
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewPullRequest {
+  pub title: String,
+  pub body: String,
+  pub source_branch: String,
+}
+
+/// Pull request (or merge request) opened in the git provider
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PullRequestDto {
+  pub number: u64,
+  pub url: String,
+}
+
+/// Commit pushed to the GitOps repository
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitDto {
+  pub branch: String,
+  pub sha: String,
+}
+
+/// How a change reached the GitOps repository
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeRequestMode {
+  PullRequest,
+  DirectCommit,
+}
+
+/// Change submitted to the GitOps repository, returned to the clients so they can link to it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChangeRequestDto {
+  pub mode: ChangeRequestMode,
+  pub branch: String,
+  pub commit_sha: String,
+  pub pull_request_number: Option<u64>,
+  pub pull_request_url: Option<String>,
+}
+
+impl ChangeRequestDto {
+  pub fn direct_commit(commit: CommitDto) -> Self {
+    Self {
+      mode: ChangeRequestMode::DirectCommit,
+      branch: commit.branch,
+      commit_sha: commit.sha,
+      pull_request_number: None,
+      pull_request_url: None,
+    }
+  }
+
+  pub fn pull_request(commit: CommitDto, pull_request: PullRequestDto) -> Self {
+    Self {
+      mode: ChangeRequestMode::PullRequest,
+      branch: commit.branch,
+      commit_sha: commit.sha,
+      pull_request_number: Some(pull_request.number),
+      pull_request_url: Some(pull_request.url),
+    }
+  }
+}
+
+/// State of a Pull Request in the git provider
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PullRequestState {
+  Open,
+  Merged,
+  /// Closed without being merged
+  Closed,
+}
+
+/// Progress of a change request, from its submission until the cluster reflects it
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeRequestState {
+  /// Waiting for the Pull Request to be merged
+  PendingPullRequest,
+  /// In the base branch, waiting for the cluster to be synced
+  Merged,
+  /// Seen in the cluster
+  Applied,
+  Failed,
+}
+
+/// Last change request submitted for a resource, followed by the reconciler
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrackedChangeDto {
+  /// Kind of the resource changed (`nodegroup`, `secret`...)
+  pub kind: String,
+  pub name: String,
+  /// Namespace of the namespaced resources, like the secrets
+  pub namespace: Option<String>,
+  /// `create`, `update` or `delete`
+  pub action: String,
+  pub change_request: ChangeRequestDto,
+  pub state: ChangeRequestState,
+  /// Why the change request failed
+  pub reason: Option<String>,
+  /// Version of the resource in the cluster when the change was submitted, replaced once the change is applied
+  pub observed_version: Option<String>,
+  /// Time (seconds since the epoch) of the change in the base branch
+  pub merged_at: Option<u64>,
+  pub submitted_at: u64,
+  pub updated_at: u64,
+}
+
+impl TrackedChangeDto {
+  /// Key of the resource the change is tracked for: `{kind}/{name}`, or `{kind}/{namespace}/{name}` when namespaced
+  pub fn key(kind: &str, name: &str, namespace: Option<&str>) -> String {
+    match namespace {
+      Some(namespace) => format!("{}/{}/{}", kind, namespace, name),
+      None => format!("{}/{}", kind, name),
+    }
+  }
+}
+
+/// Texts describing a change in the GitOps repository, rendered from the change request templates
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangeRequestMessages {
+  pub commit_message: String,
+  /// Prefix of the branch pushed for the Pull Request
+  pub branch_name: String,
+  pub pull_request_title: String,
+  pub pull_request_body: String,
+}
+
+/// Isolated working copy of the GitOps repository
+pub struct Workspace {
+  path: String,
+  /// Resources held by the version control adapter (temporary folder, concurrency permit...), released on drop
+  _lease: Box<dyn Any + Send>,
+}
+
+impl Workspace {
+  pub fn new<L: Any + Send>(path: String, lease: L) -> Self {
+    Self {
+      path,
+      _lease: Box::new(lease),
+    }
+  }
+
+  /// Local folder of the cloned repository
+  pub fn path(&self) -> &str {
+    &self.path
+  }
+}
@@ -3,9 +3,13 @@ use anyhow::Error;
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::future::Future;
 
-use crate::domain::model::{InstanceType, NodegroupRequestDto, SecretDto, SecretRequestDto};
+use crate::domain::errors::DomainError;
+use crate::domain::model::{
+  ChangeRequestDto, ChangeRequestMessages, InstanceType, JobDto, JobProgress, NodegroupDiffDto, NodegroupRequestDto, NodegroupUpdateDto,
+  RequestContext, SealingCertificateDto, SecretDto, SecretRequestDto, SecretUpdateDto, TrackedChangeDto,
+};
 use crate::domain::ports::outgoing::DataSource;
 
 #[async_trait(?Send)]
@@ -24,16 +28,73 @@ where
 {
   fn get(&self, name: &str) -> Option<K>;
   fn list(&self) -> Option<Vec<K>>;
-  fn create(&self, request: &NodegroupRequestDto) -> Result<(), anyhow::Error>;
+  /// Creates the nodegroup through GitOps. Returns the pushed change and the Pull Request opened, if any.
+  async fn create(&self, request: &NodegroupRequestDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError>;
+  /// Updates an existing nodegroup through GitOps, starting from its current spec.
+  async fn update(&self, name: &str, request: &NodegroupUpdateDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError>;
+  /// Removes the nodegroup manifest from the GitOps catalog.
+  async fn delete(&self, name: &str, skip_pull_request: bool, context: &RequestContext) -> Result<ChangeRequestDto, DomainError>;
+  /// Validates the request and renders the nodegroup manifest. Nothing is pushed to the GitOps repository.
+  fn render(&self, request: &NodegroupRequestDto) -> Result<String, DomainError>;
+  /// Compares the request with the live nodegroup in the cluster.
+  fn diff(&self, request: &NodegroupRequestDto) -> Result<NodegroupDiffDto, DomainError>;
 }
 
 /// Trait to define a Template service
 
+#[async_trait(?Send)]
 pub trait SecretService {
   fn get(&self, name: &str) -> Option<SecretDto>;
   fn list(&self) -> Option<Vec<SecretDto>>;
-  fn create(&self, request: &SecretRequestDto) -> Result<(), anyhow::Error>;
-  fn render(&self, request: &SecretRequestDto) -> Result<String, anyhow::Error>;
+  /// Creates the sealed secret through GitOps. Returns the pushed change and the Pull Request opened, if any.
+  async fn create(&self, request: &SecretRequestDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError>;
+  /// Adds, rotates or removes keys of a sealed secret of the GitOps repository, keeping the ciphertexts of the other keys
+  async fn update(&self, name: &str, update: &SecretUpdateDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError>;
+  /// Checks the request before it's queued, failing with the field errors found
+  fn validate(&self, request: &SecretRequestDto) -> Result<(), DomainError>;
+  async fn render(&self, request: &SecretRequestDto) -> Result<String, DomainError>;
+  /// Certificate of the sealed-secrets controller, to seal the secrets offline
+  async fn certificate(&self) -> Result<SealingCertificateDto, DomainError>;
+  /// Namespace the secrets go to: the requested one, or the one of the cluster
+  fn namespace(&self, requested: Option<&str>) -> String;
+}
+
+pub trait JobService {
+  /// Queues a long-running GitOps change to be run in the background. Returns the job tracking it.
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - Kind of the resource changed (`nodegroup`, `secret`...)
+  /// * `name` - Name of the resource changed
+  /// * `task` - Builds the change from the progress reporter of the job
+  ///
+  fn submit<F, T>(&self, kind: &str, name: &str, task: F) -> Result<JobDto, DomainError>
+  where
+    F: FnOnce(JobProgress) -> T + Send + 'static,
+    T: Future<Output = Result<ChangeRequestDto, DomainError>> + 'static;
+
+  fn get(&self, id: &str) -> Result<JobDto, DomainError>;
+}
+
+#[async_trait(?Send)]
+pub trait ChangeRequestTracker {
+  /// Follows a change request submitted for a resource, replacing its previous one
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - Kind of the resource changed (`nodegroup`, `secret`...)
+  /// * `name` - Name of the resource changed
+  /// * `namespace` - Namespace of the resource changed, None for the cluster-wide ones
+  /// * `action` - `create`, `update` or `delete`
+  /// * `change_request` - Commit or Pull Request submitted to the GitOps repository
+  ///
+  async fn track(&self, kind: &str, name: &str, namespace: Option<&str>, action: &str, change_request: &ChangeRequestDto);
+
+  /// Returns the last change request submitted for the resource, with its state
+  fn status(&self, kind: &str, name: &str, namespace: Option<&str>) -> Option<TrackedChangeDto>;
+
+  /// Moves the change requests in progress forward. Returns how many of them changed of state.
+  async fn reconcile(&self) -> Result<usize, DomainError>;
 }
 
 pub trait TemplateService {
@@ -42,18 +103,38 @@ pub trait TemplateService {
   /// # Arguments
   ///
   /// * `name` - A string that holds the name of template to be applied
-  /// * `values` - Map with all the key/values to be replaced in the template
+  /// * `values` - Serializable values (usually a map) to be replaced in the template
   ///
-  fn render(&self, name: &str, values: &HashMap<&str, &str>) -> Result<String, anyhow::Error>;
+  fn render<V: Serialize>(&self, name: &str, values: &V) -> Result<String, anyhow::Error>;
 
   /// Writes a rendered template to the file_path destination
   ///
   /// # Arguments
   ///
   /// * `name` - A string that holds the name of template to be applied
-  /// * `values` - Map with all the key/values to be replaced in the template
+  /// * `values` - Serializable values (usually a map) to be replaced in the template
+  ///
+  fn write_to_file<V: Serialize>(&self, name: &str, values: &V, file_path: &str) -> Result<(), Error>;
+
+  /// Renders a path from an inline template, without escaping the values. Missing values are errors.
+  ///
+  /// # Arguments
+  ///
+  /// * `template` - Handlebars template of the path, like `clusters/{{cluster}}/{{name}}.yaml`
+  /// * `values` - Serializable values (usually a map) to be replaced in the template
+  ///
+  fn render_path<V: Serialize>(&self, template: &str, values: &V) -> Result<String, Error>;
+}
+
+pub trait ChangeRequestTemplates {
+  /// Renders the commit message, branch name and Pull Request texts of a change
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - Kind of the resource changed (`nodegroup`, `secret`...), selecting the template set
+  /// * `values` - Serializable values available in the templates
   ///
-  fn write_to_file(&self, name: &str, values: &HashMap<&str, &str>, file_path: &str) -> Result<(), Error>;
+  fn compose<V: Serialize>(&self, kind: &str, values: &V) -> Result<ChangeRequestMessages, anyhow::Error>;
 }
 
 pub trait WithName {
@@ -72,5 +153,5 @@ pub trait InstanceTypesUpdater: Send {
 }
 
 pub trait InstanceTypesService: Send {
-  fn list(&self) -> Result<Vec<InstanceType>>;
+  fn list(&self) -> Result<Vec<InstanceType>, DomainError>;
 }
@@ -1,6 +1,7 @@
 use std::io::Read;
 
-use crate::domain::model::secrets::SecretRequestDto;
+use crate::domain::model::secrets::{SealedSecretDto, SealingCertificateDto, SealingTarget, SecretData};
+use crate::domain::model::{CommitDto, JobDto, NewPullRequest, PullRequestDto, PullRequestState, TrackedChangeDto, Workspace};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::Serialize;
@@ -15,6 +16,12 @@ where
   fn find_all(&self) -> Option<Vec<K>>;
 }
 
+#[async_trait(?Send)]
+pub trait NamespacedRepository<K> {
+  /// Asks the cluster for the resource in the namespace, None if it doesn't exist
+  async fn find_in(&self, namespace: &str, name: &str) -> Result<Option<K>>;
+}
+
 #[async_trait(?Send)]
 pub trait DataSource {
   fn name(&self) -> &str;
@@ -36,57 +43,114 @@ where
   fn update(&mut self, instance_types: Vec<T>) -> Result<()>;
 }
 
+/// Persistent storage of the jobs, keeping them across restarts
+pub trait JobStore {
+  /// Inserts or replaces the job with the same id
+  fn save(&self, job: &JobDto) -> Result<()>;
+  fn find(&self, id: &str) -> Result<Option<JobDto>>;
+  fn list(&self) -> Result<Vec<JobDto>>;
+}
+
+/// Persistent storage of the last change request of each resource, followed by the reconciler
+pub trait ChangeRequestStore {
+  /// Inserts or replaces the change request of the same resource (kind, name and namespace)
+  fn save(&self, change: &TrackedChangeDto) -> Result<()>;
+  fn find(&self, kind: &str, name: &str, namespace: Option<&str>) -> Result<Option<TrackedChangeDto>>;
+  fn list(&self) -> Result<Vec<TrackedChangeDto>>;
+}
+
 #[async_trait(?Send)]
 pub trait SealedSecretClient {
-  fn save(&self, request: &SecretRequestDto, destination: Option<String>) -> Result<()>;
+  fn save(
+    &self,
+    secret: &SecretData,
+    target: &SealingTarget,
+    certificate: &SealingCertificateDto,
+    destination: Option<String>,
+  ) -> Result<()>;
 
   /// Render a Selead Secret. This methis will not change or create nothing.
   ///
   /// # Arguments
   ///
-  /// * `request` - The  secret request
+  /// * `secret` - The type and values of the secret
+  /// * `target` - The namespace and scope the secret is sealed for
+  /// * `certificate` - The certificate of the sealed-secrets controller the secret is sealed for
+  ///
+  fn render(&self, secret: &SecretData, target: &SealingTarget, certificate: &SealingCertificateDto) -> Result<String>;
+
+  /// Reads the name, namespace, scope, type and keys of a sealed secret manifest
+  fn read(&self, manifest: &str) -> Result<SealedSecretDto>;
+
+  /// Seals the values of the secret into an existing sealed secret manifest and returns the updated manifest.
+  ///
+  /// # Arguments
+  ///
+  /// * `manifest` - The current sealed secret manifest
+  /// * `secret` - The keys to add or rotate, with their values
+  /// * `removed` - The keys to remove
+  /// * `target` - The namespace and scope the secret is sealed for, the ones of the manifest
+  /// * `certificate` - The certificate of the sealed-secrets controller the secret is sealed for
   ///
-  fn render(&self, request: &SecretRequestDto) -> Result<String>;
+  /// The ciphertexts of the other keys are kept as they are.
+  fn merge(
+    &self,
+    manifest: &str,
+    secret: &SecretData,
+    removed: &[String],
+    target: &SealingTarget,
+    certificate: &SealingCertificateDto,
+  ) -> Result<String>;
+}
+
+#[async_trait(?Send)]
+pub trait CertificateProvider {
+  /// Returns the certificate of the sealed-secrets controller. Fails when it has expired.
+  async fn certificate(&self) -> Result<SealingCertificateDto>;
 }
 
 #[async_trait(?Send)]
 pub trait VersionControl {
-  /// Clones a repo and returns the destination folder where the project has been cloned.
+  /// Clones the repository into a new isolated workspace, removed when the workspace is dropped.
+  /// Waits while the maximum number of concurrent git operations is reached.
+  async fn clone_repo(&self) -> Result<Workspace>;
+
+  /// Creates a commit and a push to the target remote repository. Returns the pushed commit.
   ///
   /// # Arguments
   ///
-  /// * `destination_folder` - Optional. Use this folder in case you want to override the default one
+  /// * `workspace` - The workspace where the repository has been cloned
+  /// * `message` - The commit message
   ///
+  async fn auto_commit(&self, workspace: &Workspace, message: String) -> Result<CommitDto>;
 
-  fn clone_repo(&self, destination_folder: Option<&str>) -> Result<String>;
-
-  /// Creates a commit and a push to the target remote repository.
+  /// Commits all the changes in a new branch and pushes it to the remote repository.
+  /// Returns the pushed commit and branch, that can be used as the source of a Pull Request.
   ///
   /// # Arguments
   ///
-  /// * `repository_path` - The local folder where the repository has been cloned
-  /// * `message` - The commit message
+  /// * `workspace` - The workspace where the repository has been cloned
+  /// * `commit_msg` - The commit message that will be used in the new branch
+  /// * `branch_name` - The prefix of the branch that will be created, followed by a unique suffix
   ///
-  fn auto_commit(&self, repository_path: String, message: String) -> Result<()>;
+  async fn push_branch(&self, workspace: &Workspace, commit_msg: String, branch_name: String) -> Result<CommitDto>;
+}
 
-  /// Creates a Pull Request from the local repository.
+#[async_trait(?Send)]
+pub trait PullRequestProvider {
+  /// Opens a Pull Request in the git provider from an already pushed branch against the GitOps base branch.
   ///
   /// # Arguments
   ///
-  /// * `repository_path` - The local folder where the repository has been cloned
-  /// * `pr_branch_name` - The branch that will be created to create the PR
-  /// * `commit_msg` - The commit message that will be used in the pr_branch_name
-  /// * `title` - The PR title
-  /// * `body` - The description message for the PR
+  /// * `pull_request` - Title, description and source branch of the Pull Request
   ///
-  fn pull_request(&self, repository_path: String, commit_msg: String, title: String, body: String, pr_branch_name: String) -> Result<()>;
+  async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto>;
 
-  /// Cleans the local folder where the repository has been cloned.
-  /// By default, the clean method will delete the default destintation folder used in the creation
+  /// Returns whether the Pull Request is still open, merged or closed without being merged.
   ///
   /// # Arguments
   ///
-  /// * `destination_folder` - Optional. In case you have used another destintation folder, you can use this attribute. This can be usefull when several users make actions at the same time.
+  /// * `number` - Number of the Pull Request, as returned when it was opened
   ///
-  fn clean(&self, destination_folder: Option<&str>) -> Result<()>;
+  async fn pull_request_state(&self, number: u64) -> Result<PullRequestState>;
 }
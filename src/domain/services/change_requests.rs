@@ -0,0 +1,144 @@
+use anyhow::{Error, Result};
+use handlebars::{no_escape, Handlebars};
+use serde::Serialize;
+
+use crate::domain::model::ChangeRequestMessages;
+use crate::domain::ports::incoming::ChangeRequestTemplates;
+
+const COMMIT_MESSAGE: &str = "commit_message";
+const BRANCH_NAME: &str = "branch_name";
+const PULL_REQUEST_TITLE: &str = "pull_request_title";
+const PULL_REQUEST_BODY: &str = "pull_request_body";
+
+/// Change request texts rendered from `{templates_path}/{kind}/{part}.hbs` Handlebars templates
+#[derive(Clone)]
+pub struct DefaultChangeRequestTemplates<'a> {
+  pub handlebars: Box<Handlebars<'a>>,
+}
+
+impl<'a> DefaultChangeRequestTemplates<'a> {
+  pub fn new(templates_path: &str) -> Result<Self> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(false);
+    // Plain text, not HTML
+    handlebars.register_escape_fn(no_escape);
+    handlebars.register_templates_directory(".hbs", templates_path)?;
+
+    Ok(Self {
+      handlebars: Box::new(handlebars),
+    })
+  }
+
+  fn render<V: Serialize>(&self, kind: &str, part: &str, values: &V) -> Result<String, Error> {
+    self
+      .handlebars
+      .render(&format!("{}/{}", kind, part), values)
+      .map(|text| text.trim().to_string())
+      .map_err(Error::msg)
+  }
+}
+
+impl<'a> ChangeRequestTemplates for DefaultChangeRequestTemplates<'a> {
+  fn compose<V: Serialize>(&self, kind: &str, values: &V) -> Result<ChangeRequestMessages, Error> {
+    Ok(ChangeRequestMessages {
+      commit_message: self.render(kind, COMMIT_MESSAGE, values)?,
+      branch_name: to_branch_name(&self.render(kind, BRANCH_NAME, values)?),
+      pull_request_title: self.render(kind, PULL_REQUEST_TITLE, values)?,
+      pull_request_body: self.render(kind, PULL_REQUEST_BODY, values)?,
+    })
+  }
+}
+
+/// Replaces the characters not allowed (or not convenient) in git branch names
+fn to_branch_name(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '/' {
+      true => c,
+      false => '-',
+    })
+    .collect::<String>()
+    .trim_matches(|c| c == '-' || c == '/')
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::domain::ports::incoming::ChangeRequestTemplates;
+  use crate::domain::services::change_requests::to_branch_name;
+  use crate::domain::services::DefaultChangeRequestTemplates;
+
+  fn templates() -> DefaultChangeRequestTemplates<'static> {
+    DefaultChangeRequestTemplates::new("templates/change_requests").unwrap()
+  }
+
+  #[test]
+  fn compose_a_nodegroup_update() {
+    let values = json!({
+      "action": "update",
+      "name": "batch-pool",
+      "user": "jane.doe",
+      "request": { "name": "batch-pool", "max_size": 10 },
+      "diff": "--- current\n+++ proposed\n-    max: 5\n+    max: 10\n",
+    });
+
+    let messages = templates().compose("nodegroup", &values).unwrap();
+
+    assert_eq!(messages.commit_message, "Update nodegroup batch-pool\n\nRequested by jane.doe");
+    assert_eq!(messages.branch_name, "update-nodegroup-batch-pool");
+    assert_eq!(messages.pull_request_title, "Update nodegroup batch-pool");
+    assert!(messages.pull_request_body.contains("requested by @jane.doe"));
+    assert!(messages
+      .pull_request_body
+      .contains("```diff\n--- current\n+++ proposed\n-    max: 5\n+    max: 10\n```"));
+  }
+
+  #[test]
+  fn compose_a_secret_without_user() {
+    let values = json!({
+      "action": "create",
+      "name": "db-credentials",
+      "request": { "name": "db-credentials", "keys": ["password", "username"] },
+    });
+
+    let messages = templates().compose("secret", &values).unwrap();
+
+    assert_eq!(messages.commit_message, "Add sealed secret db-credentials");
+    assert_eq!(messages.branch_name, "create-secret-db-credentials");
+    assert_eq!(messages.pull_request_title, "Add sealed secret db-credentials");
+    assert!(messages.pull_request_body.contains("`password`, `username`"));
+    assert!(!messages.pull_request_body.contains("requested by"));
+  }
+
+  #[test]
+  fn compose_a_secret_update() {
+    let values = json!({
+      "action": "update",
+      "name": "db-credentials",
+      "user": "jane.doe",
+      "request": { "name": "db-credentials", "namespace": "payments", "scope": "strict", "keys": ["api-key"], "removed": ["token"] },
+    });
+
+    let messages = templates().compose("secret", &values).unwrap();
+
+    assert_eq!(
+      messages.commit_message,
+      "Update sealed secret db-credentials\n\nRequested by jane.doe"
+    );
+    assert_eq!(messages.branch_name, "update-secret-db-credentials");
+    assert_eq!(messages.pull_request_title, "Update sealed secret db-credentials");
+    assert_eq!(
+      messages.pull_request_body,
+      "Updates the sealed secret `db-credentials`, requested by @jane.doe.\n\nNamespace: `payments` (strict)\n\
+       Added or rotated keys: `api-key`\nRemoved keys: `token`"
+    );
+  }
+
+  #[test]
+  fn sanitize_branch_names() {
+    assert_eq!(to_branch_name("update nodegroup: GPU pool!"), "update-nodegroup--GPU-pool");
+    assert_eq!(to_branch_name("feature/batch_pool"), "feature/batch_pool");
+  }
+}
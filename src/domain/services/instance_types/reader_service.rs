@@ -1,5 +1,4 @@
-use anyhow::Result;
-
+use crate::domain::errors::DomainError;
 use crate::domain::model::InstanceType;
 use crate::domain::ports::incoming::InstanceTypesService;
 use crate::domain::ports::outgoing::ReadStore;
@@ -21,7 +20,7 @@ impl<S> InstanceTypesService for DefaultInstanceTypesService<S>
 where
   S: ReadStore<InstanceType> + Send + Sync + 'static,
 {
-  fn list(&self) -> Result<Vec<InstanceType>> {
-    self.store.list()
+  fn list(&self) -> Result<Vec<InstanceType>, DomainError> {
+    self.store.list().map_err(DomainError::Store)
   }
 }
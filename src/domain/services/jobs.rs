@@ -0,0 +1,228 @@
+use anyhow::{anyhow, Result};
+use futures::future::LocalBoxFuture;
+use log::{error, info, warn};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+use tokio::task::LocalSet;
+use uuid::Uuid;
+
+use crate::domain::errors::DomainError;
+use crate::domain::model::{ChangeRequestDto, JobDto, JobProgress};
+use crate::domain::ports::incoming::JobService;
+use crate::domain::ports::outgoing::JobStore;
+
+/// Job ready to be run by a worker
+type Task = Box<dyn FnOnce() -> LocalBoxFuture<'static, ()> + Send>;
+
+/// Runs the GitOps changes in a pool of background workers, keeping their state in the job store.
+///
+/// Each worker is a thread with its own single-threaded tokio runtime, so the jobs don't need to be `Send`
+/// and never block the HTTP workers. The clones of the service share the same pool.
+#[derive(Clone)]
+pub struct DefaultJobsService<J>
+where
+  J: JobStore + Clone + Send + Sync + 'static,
+{
+  store: J,
+  sender: UnboundedSender<Task>,
+}
+
+impl<J> DefaultJobsService<J>
+where
+  J: JobStore + Clone + Send + Sync + 'static,
+{
+  /// Starts the pool of `workers` threads. The jobs left unfinished by a previous run are marked as failed,
+  /// as there is no way to resume them.
+  pub fn new(store: J, workers: usize) -> Result<Self> {
+    for mut job in store.list()?.into_iter().filter(|job| !job.is_finished()) {
+      warn!("Job {} ({} {}) interrupted by a restart", job.id, job.kind, job.name);
+      job.fail("Interrupted by a restart of the service");
+      store.save(&job)?;
+    }
+
+    let (sender, receiver) = unbounded_channel::<Task>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for index in 0..workers {
+      let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+      let receiver = receiver.clone();
+      std::thread::Builder::new()
+        .name(format!("jobs-{}", index))
+        .spawn(move || LocalSet::new().block_on(&runtime, Self::work(receiver)))?;
+    }
+    info!("Started {} job workers", workers);
+    Ok(Self { store, sender })
+  }
+
+  /// Runs the queued tasks one after the other, until the service is dropped
+  async fn work(receiver: Arc<Mutex<UnboundedReceiver<Task>>>) {
+    loop {
+      let task = receiver.lock().await.recv().await;
+      match task {
+        Some(task) => task().await,
+        None => break,
+      }
+    }
+  }
+
+  /// Applies a change to a stored job. The job keeps running if the store fails, as its change still lands.
+  fn update<U: FnOnce(&mut JobDto)>(store: &J, id: &str, change: U) {
+    let updated = store.find(id).and_then(|job| {
+      let mut job = job.ok_or_else(|| anyhow!("Job {} not found", id))?;
+      change(&mut job);
+      store.save(&job)
+    });
+    if let Err(err) = updated {
+      error!("Error updating the job {}: {}", id, err);
+    }
+  }
+}
+
+impl<J> JobService for DefaultJobsService<J>
+where
+  J: JobStore + Clone + Send + Sync + 'static,
+{
+  fn submit<F, T>(&self, kind: &str, name: &str, task: F) -> Result<JobDto, DomainError>
+  where
+    F: FnOnce(JobProgress) -> T + Send + 'static,
+    T: Future<Output = Result<ChangeRequestDto, DomainError>> + 'static,
+  {
+    let job = JobDto::new(Uuid::new_v4().to_string(), kind, name);
+    self.store.save(&job).map_err(DomainError::Store)?;
+
+    let store = self.store.clone();
+    let id = job.id.clone();
+    let run: Task = Box::new(move || {
+      Box::pin(async move {
+        let (listener_store, listener_id) = (store.clone(), id.clone());
+        let progress = JobProgress::new(&id, move |step| {
+          Self::update(&listener_store, &listener_id, |job| job.advance(step))
+        });
+        Self::update(&store, &id, JobDto::start);
+        match task(progress).await {
+          Ok(change_request) => Self::update(&store, &id, |job| job.succeed(change_request)),
+          Err(err) => {
+            warn!("Job {} failed: {}", id, err);
+            Self::update(&store, &id, |job| job.fail(err))
+          }
+        }
+      })
+    });
+    self
+      .sender
+      .send(run)
+      .map_err(|_| DomainError::Internal(anyhow!("The job workers are stopped")))?;
+    Ok(job)
+  }
+
+  fn get(&self, id: &str) -> Result<JobDto, DomainError> {
+    self
+      .store
+      .find(id)
+      .map_err(DomainError::Store)?
+      .ok_or_else(|| DomainError::NotFound(format!("Job {}", id)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use std::collections::HashMap;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  use crate::domain::errors::DomainError;
+  use crate::domain::model::jobs::JobStatus;
+  use crate::domain::model::{ChangeRequestDto, CommitDto, JobDto, JobStep};
+  use crate::domain::ports::incoming::JobService;
+  use crate::domain::ports::outgoing::JobStore;
+  use crate::domain::services::jobs::DefaultJobsService;
+
+  #[derive(Clone, Default)]
+  struct JobStoreMock(Arc<Mutex<HashMap<String, JobDto>>>);
+
+  impl JobStore for JobStoreMock {
+    fn save(&self, job: &JobDto) -> Result<()> {
+      self.0.lock().unwrap().insert(job.id.clone(), job.clone());
+      Ok(())
+    }
+
+    fn find(&self, id: &str) -> Result<Option<JobDto>> {
+      Ok(self.0.lock().unwrap().get(id).cloned())
+    }
+
+    fn list(&self) -> Result<Vec<JobDto>> {
+      Ok(self.0.lock().unwrap().values().cloned().collect())
+    }
+  }
+
+  fn wait_for<S: JobService>(service: &S, id: &str) -> JobDto {
+    for _ in 0..100 {
+      let job = service.get(id).unwrap();
+      if job.is_finished() {
+        return job;
+      }
+      std::thread::sleep(Duration::from_millis(20));
+    }
+    panic!("The job {} didn't finish", id)
+  }
+
+  fn steps(job: &JobDto) -> Vec<JobStep> {
+    job.steps.iter().map(|step| step.step).collect()
+  }
+
+  #[test]
+  fn run_the_jobs_in_the_background() {
+    let service = DefaultJobsService::new(JobStoreMock::default(), 2).unwrap();
+
+    let job = service
+      .submit("nodegroup", "pool", |progress| async move {
+        progress.report(JobStep::Cloned);
+        progress.report(JobStep::Pushed);
+        Ok(ChangeRequestDto::direct_commit(CommitDto {
+          branch: "dev".to_string(),
+          sha: "abc".to_string(),
+        }))
+      })
+      .unwrap();
+    assert_eq!(job.status, JobStatus::Queued);
+
+    let job = wait_for(&service, &job.id);
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(steps(&job), vec![JobStep::Queued, JobStep::Cloned, JobStep::Pushed]);
+    assert_eq!(job.result.unwrap().commit_sha, "abc");
+  }
+
+  #[test]
+  fn record_the_failures() {
+    let service = DefaultJobsService::new(JobStoreMock::default(), 1).unwrap();
+
+    let job = service
+      .submit("secret", "token", |progress| async move {
+        progress.report(JobStep::Cloned);
+        Err(DomainError::Conflict("The changes conflict".to_string()))
+      })
+      .unwrap();
+
+    let job = wait_for(&service, &job.id);
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(steps(&job), vec![JobStep::Queued, JobStep::Cloned, JobStep::Failed]);
+    assert_eq!(job.error.as_deref(), Some("Conflict: The changes conflict"));
+    assert!(matches!(service.get("unknown"), Err(DomainError::NotFound(_))));
+  }
+
+  #[test]
+  fn fail_the_jobs_interrupted_by_a_restart() {
+    let store = JobStoreMock::default();
+    let mut running = JobDto::new("running".to_string(), "nodegroup", "pool");
+    running.start();
+    store.save(&running).unwrap();
+
+    let service = DefaultJobsService::new(store, 1).unwrap();
+
+    let job = service.get("running").unwrap();
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(steps(&job), vec![JobStep::Queued, JobStep::Failed]);
+  }
+}
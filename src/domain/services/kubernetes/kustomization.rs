@@ -0,0 +1,279 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// Names of the kustomization file looked up by kustomize, in its order
+const KUSTOMIZATION_FILES: [&str; 3] = ["kustomization.yaml", "kustomization.yml", "Kustomization"];
+
+/// Item of the `resources` list, with the comment lines right above it
+struct Resource {
+  comments: Vec<String>,
+  line: String,
+  value: String,
+}
+
+/// Adds the manifest to the `resources` of the kustomization of its folder, if there is one
+pub fn add_to_kustomization(manifest_path: &str) -> Result<()> {
+  edit_kustomization(manifest_path, add_resource)
+}
+
+/// Removes the manifest from the `resources` of the kustomization of its folder, if there is one
+pub fn remove_from_kustomization(manifest_path: &str) -> Result<()> {
+  edit_kustomization(manifest_path, remove_resource)
+}
+
+fn edit_kustomization<E: Fn(&str, &str) -> String>(manifest_path: &str, edit: E) -> Result<()> {
+  let manifest = Path::new(manifest_path);
+  let (folder, resource) = match (manifest.parent(), manifest.file_name().and_then(|name| name.to_str())) {
+    (Some(folder), Some(resource)) => (folder, resource),
+    _ => return Ok(()),
+  };
+  let kustomization = match KUSTOMIZATION_FILES.iter().map(|name| folder.join(name)).find(|path| path.is_file()) {
+    Some(kustomization) => kustomization,
+    None => return Ok(()),
+  };
+
+  let content = fs::read_to_string(&kustomization)?;
+  let edited = edit(&content, resource);
+  if edited != content {
+    fs::write(&kustomization, edited)?;
+  }
+  Ok(())
+}
+
+/// Adds the resource to the `resources` list, keeping it sorted. The rest of the kustomization is left untouched.
+pub fn add_resource(content: &str, resource: &str) -> String {
+  edit_resources(content, |resources, indent| {
+    if resources.iter().any(|item| item.value == resource) {
+      return false;
+    }
+    resources.push(Resource {
+      comments: vec![],
+      line: format!("{}- {}", indent, resource),
+      value: resource.to_string(),
+    });
+    resources.sort_by(|left, right| left.value.cmp(&right.value));
+    true
+  })
+}
+
+/// Removes the resource, and the comments right above it, from the `resources` list
+pub fn remove_resource(content: &str, resource: &str) -> String {
+  edit_resources(content, |resources, _| {
+    let count = resources.len();
+    resources.retain(|item| item.value != resource);
+    resources.len() != count
+  })
+}
+
+/// Rewrites the `resources` list after the edit, when it changes it. A flow list (`[a.yaml, b.yaml]`) is rewritten
+/// as a block list, and a missing list is appended to the end of the file.
+fn edit_resources<E: FnOnce(&mut Vec<Resource>, &str) -> bool>(content: &str, edit: E) -> String {
+  let lines = content.lines().collect::<Vec<_>>();
+  let header = lines.iter().position(|line| resources_header(line).is_some());
+  // Header line kept as is, with its comment, when the list is a block list
+  let mut block_header = None;
+  let (start, end, mut resources, indent) = match header {
+    Some(header) => match resources_header(lines[header]).filter(|rest| rest.starts_with('[')) {
+      Some(flow) => (header, header + 1, flow_resources(flow), String::new()),
+      None => {
+        block_header = Some(lines[header]);
+        let (end, resources, indent) = block_resources(&lines, header);
+        (header, end, resources, indent)
+      }
+    },
+    None => (lines.len(), lines.len(), vec![], String::new()),
+  };
+  if !edit(&mut resources, &indent) {
+    return content.to_string();
+  }
+
+  let mut edited = lines[..start].iter().map(|line| line.to_string()).collect::<Vec<_>>();
+  edited.push(match block_header {
+    _ if resources.is_empty() => "resources: []".to_string(),
+    Some(block_header) => block_header.to_string(),
+    None => "resources:".to_string(),
+  });
+  for resource in resources {
+    edited.extend(resource.comments);
+    edited.push(resource.line);
+  }
+  edited.extend(lines[end..].iter().map(|line| line.to_string()));
+
+  let mut edited = edited.join("\n");
+  if content.ends_with('\n') || header.is_none() {
+    edited.push('\n');
+  }
+  edited
+}
+
+/// Rest of the line after `resources:`, when the line starts the top-level `resources` key
+fn resources_header(line: &str) -> Option<&str> {
+  let rest = line.strip_prefix("resources:")?.trim();
+  match rest.is_empty() || rest.starts_with('#') || rest.starts_with('[') {
+    true => Some(rest),
+    false => None,
+  }
+}
+
+fn flow_resources(flow: &str) -> Vec<Resource> {
+  let items = flow.trim_start_matches('[').split(']').next().unwrap_or_default();
+  items
+    .split(',')
+    .map(unquote)
+    .filter(|value| !value.is_empty())
+    .map(|value| Resource {
+      comments: vec![],
+      line: format!("- {}", value),
+      value: value.to_string(),
+    })
+    .collect()
+}
+
+/// Items of the block list following the header, with the end of the list and the indentation of its items.
+/// The comments and blank lines after the last item don't belong to the list.
+fn block_resources(lines: &[&str], header: usize) -> (usize, Vec<Resource>, String) {
+  let mut resources = vec![];
+  let mut indent = None;
+  let mut comments = vec![];
+  let mut end = header + 1;
+  for (index, line) in lines.iter().enumerate().skip(header + 1) {
+    let trimmed = line.trim_start();
+    let line_indent = &line[..line.len() - trimmed.len()];
+    match item_value(trimmed) {
+      Some(value) if indent.is_none() || indent == Some(line_indent) => {
+        indent = Some(line_indent);
+        resources.push(Resource {
+          comments: std::mem::take(&mut comments),
+          line: line.to_string(),
+          value: value.to_string(),
+        });
+        end = index + 1;
+      }
+      None if trimmed.is_empty() || trimmed.starts_with('#') => comments.push(line.to_string()),
+      _ => break,
+    }
+  }
+  (end, resources, indent.unwrap_or_default().to_string())
+}
+
+/// Value of a list item (`- value # comment`)
+fn item_value(line: &str) -> Option<&str> {
+  let item = line.strip_prefix('-')?;
+  if !item.is_empty() && !item.starts_with(' ') {
+    return None;
+  }
+  Some(unquote(item.split(" #").next().unwrap_or_default()))
+}
+
+fn unquote(value: &str) -> &str {
+  value.trim().trim_matches(|c| c == '"' || c == '\'')
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use tempfile::TempDir;
+
+  use crate::domain::services::kubernetes::kustomization::{
+    add_resource, add_to_kustomization, remove_from_kustomization, remove_resource,
+  };
+
+  const KUSTOMIZATION: &str = r#"apiVersion: kustomize.config.k8s.io/v1beta1
+kind: Kustomization
+# Managed nodegroups
+resources:
+  - nodegroup-a.yaml
+  # The GPU pool
+  - nodegroup-gpu.yaml # reserved
+  - "nodegroup-z.yaml"
+
+commonLabels:
+  team: platform
+"#;
+
+  #[test]
+  fn add_the_resource_in_order() {
+    let edited = add_resource(KUSTOMIZATION, "nodegroup-batch.yaml");
+
+    assert_eq!(
+      edited,
+      r#"apiVersion: kustomize.config.k8s.io/v1beta1
+kind: Kustomization
+# Managed nodegroups
+resources:
+  - nodegroup-a.yaml
+  - nodegroup-batch.yaml
+  # The GPU pool
+  - nodegroup-gpu.yaml # reserved
+  - "nodegroup-z.yaml"
+
+commonLabels:
+  team: platform
+"#
+    );
+    assert_eq!(add_resource(&edited, "nodegroup-batch.yaml"), edited);
+  }
+
+  #[test]
+  fn remove_the_resource_with_its_comments() {
+    let edited = remove_resource(KUSTOMIZATION, "nodegroup-gpu.yaml");
+
+    assert_eq!(
+      edited,
+      r#"apiVersion: kustomize.config.k8s.io/v1beta1
+kind: Kustomization
+# Managed nodegroups
+resources:
+  - nodegroup-a.yaml
+  - "nodegroup-z.yaml"
+
+commonLabels:
+  team: platform
+"#
+    );
+    assert_eq!(remove_resource(&edited, "nodegroup-gpu.yaml"), edited);
+  }
+
+  #[test]
+  fn rewrite_the_flow_and_missing_lists() {
+    let flow = "kind: Kustomization\nresources: [b.yaml, 'd.yaml']\nnamespace: infra\n";
+    assert_eq!(
+      add_resource(flow, "c.yaml"),
+      "kind: Kustomization\nresources:\n- b.yaml\n- c.yaml\n- d.yaml\nnamespace: infra\n"
+    );
+
+    let missing = "kind: Kustomization\nnamespace: infra";
+    assert_eq!(
+      add_resource(missing, "a.yaml"),
+      "kind: Kustomization\nnamespace: infra\nresources:\n- a.yaml\n"
+    );
+    assert_eq!(remove_resource(missing, "a.yaml"), missing);
+
+    let single = "kind: Kustomization\nresources:\n- a.yaml\n";
+    assert_eq!(remove_resource(single, "a.yaml"), "kind: Kustomization\nresources: []\n");
+  }
+
+  #[test]
+  fn edit_the_sibling_kustomization_only() {
+    let folder = TempDir::new().unwrap();
+    let kustomized = folder.path().join("kustomized");
+    fs::create_dir_all(&kustomized).unwrap();
+    fs::write(kustomized.join("kustomization.yml"), "resources:\n- a.yaml\n").unwrap();
+    let manifest = kustomized.join("b.yaml").to_string_lossy().into_owned();
+
+    add_to_kustomization(&manifest).unwrap();
+    assert_eq!(
+      fs::read_to_string(kustomized.join("kustomization.yml")).unwrap(),
+      "resources:\n- a.yaml\n- b.yaml\n"
+    );
+    remove_from_kustomization(&manifest).unwrap();
+    assert_eq!(
+      fs::read_to_string(kustomized.join("kustomization.yml")).unwrap(),
+      "resources:\n- a.yaml\n"
+    );
+
+    add_to_kustomization(&folder.path().join("b.yaml").to_string_lossy()).unwrap();
+    assert_eq!(fs::read_dir(folder.path()).unwrap().count(), 1);
+  }
+}
@@ -0,0 +1,116 @@
+use serde_json::json;
+use std::path::Path;
+
+use crate::domain::errors::{DomainError, FieldError};
+use crate::domain::model::{ManifestLayout, Workspace};
+use crate::domain::ports::incoming::TemplateService;
+use crate::utils::validators::paths::{is_contained_path, is_path_component};
+
+/// Renders the path of the manifest of a resource from the template of its kind, and creates its folder in the workspace.
+///
+/// The name comes from the request, so it must be a single path component, and the rendered path must stay inside the
+/// repository.
+///
+/// # Arguments
+///
+/// * `template_service` - Service rendering the path template
+/// * `layout` - Layout of the manifests in the GitOps repository of the cluster
+/// * `kind` - Kind of the resource (`nodegroup` or `secret`)
+/// * `name` - Name of the resource, as requested
+/// * `namespace` - Namespace of the resource, if it's namespaced, exposed to the template as `namespace`
+/// * `workspace` - Workspace of the cloned repository
+///
+pub fn manifest_path<T: TemplateService>(
+  template_service: &T,
+  layout: &ManifestLayout,
+  kind: &str,
+  name: &str,
+  namespace: Option<&str>,
+  workspace: &Workspace,
+) -> Result<String, DomainError> {
+  if !is_path_component(name) {
+    return Err(DomainError::Validation(vec![FieldError::new(
+      "name",
+      "must not contain path separators nor be . or ..",
+    )]));
+  }
+  let template = match kind {
+    "nodegroup" => &layout.nodegroup,
+    "secret" => &layout.secret,
+    _ => return Err(DomainError::Internal(anyhow::anyhow!("No manifest path for the kind {}", kind))),
+  };
+  let mut values = json!({ "cluster": layout.cluster, "kind": kind, "name": name });
+  if let Some(namespace) = namespace {
+    values["namespace"] = json!(namespace);
+  }
+  let relative_path = template_service.render_path(template, &values).map_err(DomainError::Internal)?;
+  if !is_contained_path(&relative_path) {
+    return Err(DomainError::BadRequest(format!(
+      "The manifest path {} is outside of the GitOps repository",
+      relative_path
+    )));
+  }
+
+  let path = Path::new(workspace.path()).join(&relative_path);
+  if let Some(folder) = path.parent() {
+    std::fs::create_dir_all(folder).map_err(|err| DomainError::Internal(err.into()))?;
+  }
+  Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use tempfile::TempDir;
+
+  use crate::domain::errors::DomainError;
+  use crate::domain::model::{ManifestLayout, Workspace};
+  use crate::domain::services::kubernetes::manifests::manifest_path;
+  use crate::domain::services::DefaultTemplateService;
+
+  fn workspace(folder: &TempDir) -> Workspace {
+    Workspace::new(folder.path().to_string_lossy().into_owned(), ())
+  }
+
+  #[test]
+  fn render_the_path_of_the_kind() {
+    let folder = TempDir::new().unwrap();
+    let layout = ManifestLayout {
+      nodegroup: "clusters/{{cluster}}/nodegroups/{{name}}.yaml".to_string(),
+      ..ManifestLayout::in_catalog("staging", "catalog")
+    };
+    let template_service = DefaultTemplateService::new().unwrap();
+
+    let path = manifest_path(&template_service, &layout, "nodegroup", "pool", None, &workspace(&folder)).unwrap();
+    assert_eq!(path, folder.path().join("clusters/staging/nodegroups/pool.yaml").to_string_lossy());
+    assert!(folder.path().join("clusters/staging/nodegroups").is_dir());
+
+    let path = manifest_path(&template_service, &layout, "secret", "token", Some("payments"), &workspace(&folder)).unwrap();
+    assert_eq!(path, folder.path().join("catalog/sealed-secret-token.yaml").to_string_lossy());
+
+    let layout = ManifestLayout {
+      secret: "clusters/{{cluster}}/{{namespace}}/{{name}}.yaml".to_string(),
+      ..layout
+    };
+    let path = manifest_path(&template_service, &layout, "secret", "token", Some("payments"), &workspace(&folder)).unwrap();
+    assert_eq!(path, folder.path().join("clusters/staging/payments/token.yaml").to_string_lossy());
+  }
+
+  #[test]
+  fn keep_the_manifests_inside_the_repository() {
+    let folder = TempDir::new().unwrap();
+    let template_service = DefaultTemplateService::new().unwrap();
+    let layout = ManifestLayout::in_catalog("staging", "catalog");
+
+    let traversal = manifest_path(&template_service, &layout, "secret", "../../etc/passwd", None, &workspace(&folder));
+    assert!(matches!(traversal, Err(DomainError::Validation(_))));
+    let traversal = manifest_path(&template_service, &layout, "secret", "..", None, &workspace(&folder));
+    assert!(matches!(traversal, Err(DomainError::Validation(_))));
+
+    let layout = ManifestLayout {
+      secret: "/etc/{{name}}.yaml".to_string(),
+      ..layout
+    };
+    let absolute = manifest_path(&template_service, &layout, "secret", "token", None, &workspace(&folder));
+    assert!(matches!(absolute, Err(DomainError::BadRequest(_))));
+  }
+}
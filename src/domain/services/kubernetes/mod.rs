@@ -1,5 +1,5 @@
+pub mod kustomization;
+pub mod manifests;
 pub mod nodegroups;
 pub mod secrets;
-
-pub use nodegroups::DefaultNodegroupsService;
-pub use secrets::DefaultSecretsService;
+pub mod validation;
@@ -1,38 +1,71 @@
+use crate::domain::errors::{DomainError, FieldError};
 use crate::domain::model::NodeGroupDto;
-use crate::domain::ports::incoming::{NodegroupService, TemplateService};
-use crate::domain::ports::outgoing::{Repository, VersionControl};
+use crate::domain::ports::incoming::{ChangeRequestTemplates, NodegroupService, TemplateService};
+use crate::domain::ports::outgoing::{PullRequestProvider, Repository, VersionControl};
 use anyhow::Result;
-use std::collections::HashMap;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use similar::TextDiff;
+use std::path::Path;
 
-use crate::domain::model::{InstanceType, NodegroupRequestDto};
+use crate::domain::model::{
+  ChangeRequestDto, ChangeRequestMessages, FieldChangeDto, InstanceType, JobStep, ManifestLayout, NewPullRequest, NodegroupDiffDto,
+  NodegroupRequestDto, NodegroupUpdateDto, RequestContext, Workspace,
+};
 use crate::domain::ports::outgoing::ReadStore;
+use crate::domain::services::kubernetes::kustomization::{add_to_kustomization, remove_from_kustomization};
+use crate::domain::services::kubernetes::manifests::manifest_path;
+use crate::domain::services::kubernetes::validation::validate_nodegroup_request;
+use crate::utils::memory::MemoryQuantity;
 
-pub struct DefaultNodegroupsService<R, S, T, V>
+const DEFAULT_LIFECYCLE: &str = "ondemand";
+const DEFAULT_SPOT_ALLOCATION_STRATEGY: &str = "capacity-optimized";
+const DEFAULT_AVAILABILITY_ZONE_NUMBER: usize = 1;
+
+pub struct DefaultNodegroupsService<R, S, T, V, P, C>
 where
   R: Repository<NodeGroupDto>,
   S: ReadStore<InstanceType> + Send + Sync + 'static,
   T: TemplateService,
   V: VersionControl,
+  P: PullRequestProvider,
+  C: ChangeRequestTemplates,
 {
   repository: R,
   store: S,
   template_service: T,
   gitops_service: V,
+  pull_request_provider: P,
+  change_request_templates: C,
+  layout: ManifestLayout,
 }
 
-impl<R, S, T, V> DefaultNodegroupsService<R, S, T, V>
+impl<R, S, T, V, P, C> DefaultNodegroupsService<R, S, T, V, P, C>
 where
   R: Repository<NodeGroupDto>,
   S: ReadStore<InstanceType> + Send + Sync + 'static,
   T: TemplateService,
   V: VersionControl,
+  P: PullRequestProvider,
+  C: ChangeRequestTemplates,
 {
-  pub fn new(repository: R, store: S, template_service: T, gitops_service: V) -> Self {
+  pub fn new(
+    repository: R,
+    store: S,
+    template_service: T,
+    gitops_service: V,
+    pull_request_provider: P,
+    change_request_templates: C,
+    layout: ManifestLayout,
+  ) -> Self {
     Self {
       repository,
       store,
       template_service,
       gitops_service,
+      pull_request_provider,
+      change_request_templates,
+      layout,
     }
   }
 
@@ -53,20 +86,148 @@ where
     }
   }
 
-  fn get_or_default<'a>(value: &'a Option<String>, default_value: &'a str) -> &'a str {
-    match value {
-      Some(v) => v.as_str(),
-      None => default_value,
+  /// Renders the nodegroup manifest into the cloned repository, at the path of the layout, and lists it in the
+  /// kustomization of its folder
+  fn write_manifest(&self, workspace: &Workspace, request: &NodegroupRequestDto) -> Result<(), DomainError> {
+    let destination_path = manifest_path(&self.template_service, &self.layout, "nodegroup", &request.name, None, workspace)?;
+    self
+      .template_service
+      .write_to_file("nodegroup", &Self::template_values(request), &destination_path)
+      .and_then(|_| add_to_kustomization(&destination_path))
+      .map_err(DomainError::Internal)
+  }
+
+  /// Pushes the changes of the cloned repository, either directly to the base branch or through a Pull Request
+  async fn submit(
+    &self,
+    workspace: &Workspace,
+    skip_pull_request: bool,
+    messages: ChangeRequestMessages,
+    context: &RequestContext,
+  ) -> Result<ChangeRequestDto, DomainError> {
+    if skip_pull_request {
+      let commit = self
+        .gitops_service
+        .auto_commit(workspace, messages.commit_message)
+        .await
+        .map_err(DomainError::from_git)?;
+      context.report(JobStep::Pushed);
+      return Ok(ChangeRequestDto::direct_commit(commit));
     }
+
+    let commit = self
+      .gitops_service
+      .push_branch(workspace, messages.commit_message, messages.branch_name)
+      .await
+      .map_err(DomainError::from_git)?;
+    context.report(JobStep::Pushed);
+    let pull_request = NewPullRequest {
+      title: messages.pull_request_title,
+      body: messages.pull_request_body,
+      source_branch: commit.branch.clone(),
+    };
+    let pull_request = self
+      .pull_request_provider
+      .create_pull_request(&pull_request)
+      .await
+      .map_err(DomainError::from_git)?;
+    context.report(JobStep::PullRequestOpened);
+    Ok(ChangeRequestDto::pull_request(commit, pull_request))
+  }
+
+  /// Compares the request with the live nodegroup, if any, rendering both through the nodegroup template
+  fn build_diff(&self, request: &NodegroupRequestDto) -> Result<NodegroupDiffDto, DomainError> {
+    let proposed = with_defaults(request);
+    let current = self
+      .repository
+      .find_by(&request.name)
+      .and_then(|current| updated_request(&current, &NodegroupUpdateDto::default()).ok())
+      .map(|current| with_defaults(&current));
+
+    let proposed_manifest = self
+      .template_service
+      .render("nodegroup", &Self::template_values(&proposed))
+      .map_err(DomainError::Internal)?;
+    let current_manifest = match &current {
+      Some(current) => self
+        .template_service
+        .render("nodegroup", &Self::template_values(current))
+        .map_err(DomainError::Internal)?,
+      None => String::new(),
+    };
+    let unified_diff = TextDiff::from_lines(&current_manifest, &proposed_manifest)
+      .unified_diff()
+      .header("current", "proposed")
+      .to_string();
+
+    Ok(NodegroupDiffDto {
+      name: request.name.clone(),
+      changes: field_changes(current.as_ref(), &proposed),
+      unified_diff,
+    })
+  }
+
+  /// Renders the commit message, branch and Pull Request texts from the `nodegroup` change request templates
+  fn messages(
+    &self,
+    action: &str,
+    name: &str,
+    context: &RequestContext,
+    request: Option<&NodegroupRequestDto>,
+    diff: Option<&NodegroupDiffDto>,
+  ) -> Result<ChangeRequestMessages, DomainError> {
+    let diff = diff.filter(|diff| !diff.changes.is_empty());
+    let values = json!({
+      "action": action,
+      "name": name,
+      "user": context.user,
+      "request": request,
+      "changes": diff.map(|diff| &diff.changes),
+      "diff": diff.map(|diff| &diff.unified_diff),
+    });
+    self
+      .change_request_templates
+      .compose("nodegroup", &values)
+      .map_err(DomainError::Internal)
+  }
+
+  /// Values used to render the nodegroup template
+  fn template_values(request: &NodegroupRequestDto) -> Value {
+    json!({
+      "NAME": request.name,
+      "EPHEMERAL_STORAGE": request.ephemeral.as_str(),
+      "DEFAULT_INSTANCE_TYPE": request.default_instance_type,
+      "ALTERNATE_INSTANCE_TYPES": request.alternate_instance_types.join(", "),
+      "MIN_SIZE": request.min_size.unwrap_or(0),
+      "MAX_SIZE": request.max_size.unwrap_or(0),
+      "TARGET_SIZE": request.target_size.unwrap_or(0),
+      "LIFECYCLE": request.lifecycle.as_deref().unwrap_or(DEFAULT_LIFECYCLE),
+      "SPOT_ALLOCATION_STRATEGY": request.spot_allocation_strategy.as_deref().unwrap_or(DEFAULT_SPOT_ALLOCATION_STRATEGY),
+      "AVAILABILITY_ZONE_NUMBER": request.availability_zone_number.unwrap_or(DEFAULT_AVAILABILITY_ZONE_NUMBER),
+      // Rendered as YAML block entries, in the order of the request, with the user values quoted
+      "LABELS": request
+        .labels
+        .iter()
+        .map(|(key, value)| json!({ "key": quoted(key), "value": quoted(value) }))
+        .collect::<Vec<Value>>(),
+      "TAINTS": request
+        .taints
+        .iter()
+        .map(|taint| json!({ "key": quoted(&taint.key), "value": taint.value.as_deref().map(quoted), "effect": quoted(&taint.effect) }))
+        .collect::<Vec<Value>>(),
+    })
   }
 }
 
-impl<R, S, T, V> NodegroupService<NodeGroupDto> for DefaultNodegroupsService<R, S, T, V>
+#[async_trait(?Send)]
+impl<R, S, T, V, P, C> NodegroupService<NodeGroupDto> for DefaultNodegroupsService<R, S, T, V, P, C>
 where
   R: Repository<NodeGroupDto>,
   S: ReadStore<InstanceType> + Send + Sync + 'static,
   T: TemplateService + Send + 'static,
   V: VersionControl + Send,
+  P: PullRequestProvider + Send,
+  C: ChangeRequestTemplates + Send,
 {
   fn get(&self, name: &str) -> Option<NodeGroupDto> {
     self.repository.find_by(name).map(|nodegroup| self.populate_nodegroup(nodegroup))
@@ -81,41 +242,428 @@ where
     })
   }
 
-  fn create(&self, request: &NodegroupRequestDto) -> Result<(), anyhow::Error> {
-    // Validation missing yet. PoC only
+  async fn create(&self, request: &NodegroupRequestDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError> {
+    validate_nodegroup_request(request, &self.store)?;
+    let diff = self.build_diff(request)?;
+    let messages = self.messages("create", &request.name, context, Some(request), Some(&diff))?;
+
+    let workspace = self.gitops_service.clone_repo().await.map_err(DomainError::from_git)?;
+    context.report(JobStep::Cloned);
+    self.write_manifest(&workspace, request)?;
+    context.report(JobStep::Rendered);
+    self.submit(&workspace, request.skip_pull_request, messages, context).await
+  }
+
+  async fn update(&self, name: &str, request: &NodegroupUpdateDto, context: &RequestContext) -> Result<ChangeRequestDto, DomainError> {
+    let current = self
+      .repository
+      .find_by(name)
+      .ok_or_else(|| DomainError::NotFound(format!("NodeGroup {}", name)))?;
+    let request = updated_request(&current, request)?;
+    validate_nodegroup_request(&request, &self.store)?;
+    let diff = self.build_diff(&request)?;
+    let messages = self.messages("update", name, context, Some(&request), Some(&diff))?;
 
-    let mut data = HashMap::new();
-    data.insert("NAME", request.name.as_str());
-    data.insert("EPHEMERAL_STORAGE", request.ephemeral.as_str());
-    data.insert("DEFAULT_INSTANCE_TYPE", request.default_instance_type.as_str());
-    data.insert("MIN_SIZE", Self::get_or_default(&request.min_size, "0"));
-    data.insert("MAX_SIZE", Self::get_or_default(&request.max_size, "0"));
-    data.insert("TARGET_SIZE", Self::get_or_default(&request.target_size, "0"));
-    if let Some(alternate_instance_type) = &request.alternate_instance_type {
-      data.insert("ALTERNATE_INSTANCE_TYPE", alternate_instance_type.as_str());
+    let workspace = self.gitops_service.clone_repo().await.map_err(DomainError::from_git)?;
+    context.report(JobStep::Cloned);
+    self.write_manifest(&workspace, &request)?;
+    context.report(JobStep::Rendered);
+    self.submit(&workspace, request.skip_pull_request, messages, context).await
+  }
+
+  async fn delete(&self, name: &str, skip_pull_request: bool, context: &RequestContext) -> Result<ChangeRequestDto, DomainError> {
+    let messages = self.messages("delete", name, context, None, None)?;
+    let workspace = self.gitops_service.clone_repo().await.map_err(DomainError::from_git)?;
+    context.report(JobStep::Cloned);
+    let manifest_path = manifest_path(&self.template_service, &self.layout, "nodegroup", name, None, &workspace)?;
+    if !Path::new(&manifest_path).exists() {
+      return Err(DomainError::NotFound(format!("NodeGroup {} in the GitOps repository", name)));
     }
+    std::fs::remove_file(&manifest_path).map_err(|err| DomainError::Internal(err.into()))?;
+    remove_from_kustomization(&manifest_path).map_err(DomainError::Internal)?;
+    context.report(JobStep::Rendered);
+    self.submit(&workspace, skip_pull_request, messages, context).await
+  }
 
+  fn render(&self, request: &NodegroupRequestDto) -> Result<String, DomainError> {
+    validate_nodegroup_request(request, &self.store)?;
     self
-      .gitops_service
-      .clone_repo(None)
-      .and_then(|gitops_path| {
-        let destination_path = format!("{}/infrastructure/_catalog/templates/nodegroup-{}.yaml", gitops_path, request.name);
-        self
-          .template_service
-          .write_to_file("nodegroup", &data, &*destination_path)
-          .map(|_| gitops_path)
+      .template_service
+      .render("nodegroup", &Self::template_values(request))
+      .map_err(DomainError::Internal)
+  }
+
+  fn diff(&self, request: &NodegroupRequestDto) -> Result<NodegroupDiffDto, DomainError> {
+    validate_nodegroup_request(request, &self.store)?;
+    self.build_diff(request)
+  }
+}
+
+/// JSON strings are valid YAML double-quoted scalars
+fn quoted(value: &str) -> String {
+  Value::String(value.to_string()).to_string()
+}
+
+/// Builds the full request of an update, taking the missing values from the current spec
+fn updated_request(current: &NodeGroupDto, update: &NodegroupUpdateDto) -> Result<NodegroupRequestDto, DomainError> {
+  let default_instance_type = update
+    .default_instance_type
+    .clone()
+    .or_else(|| current.instance_name.clone())
+    .ok_or_else(|| DomainError::Validation(vec![FieldError::new("default_instance_type", "is required")]))?;
+  let ephemeral = match &update.ephemeral {
+    Some(ephemeral) => ephemeral.clone(),
+    None => current
+      .ephemeral
+      .as_deref()
+      .and_then(|ephemeral| ephemeral.parse::<MemoryQuantity>().ok())
+      .ok_or_else(|| DomainError::Validation(vec![FieldError::new("ephemeral", "is required")]))?,
+  };
+
+  Ok(NodegroupRequestDto {
+    name: current.name.clone(),
+    default_instance_type,
+    alternate_instance_types: update
+      .alternate_instance_types
+      .clone()
+      .unwrap_or_else(|| current.alternate_instance_names.clone()),
+    min_size: update.min_size.or(current.min_size),
+    max_size: update.max_size.or(current.max_size),
+    target_size: update.target_size.or(current.target_size),
+    ephemeral,
+    lifecycle: update.lifecycle.clone().or_else(|| current.lifecycle.clone()),
+    spot_allocation_strategy: update
+      .spot_allocation_strategy
+      .clone()
+      .or_else(|| current.spot_allocation_strategy.clone()),
+    availability_zone_number: update.availability_zone_number.or(current.availability_zone_number),
+    labels: update.labels.clone().unwrap_or_else(|| current.labels.clone()),
+    taints: update.taints.clone().unwrap_or_else(|| current.taints.clone()),
+    skip_pull_request: update.skip_pull_request,
+  })
+}
+
+/// Fills the optional values of the request with the defaults applied by the template
+fn with_defaults(request: &NodegroupRequestDto) -> NodegroupRequestDto {
+  NodegroupRequestDto {
+    lifecycle: request.lifecycle.clone().or_else(|| Some(DEFAULT_LIFECYCLE.to_string())),
+    spot_allocation_strategy: request
+      .spot_allocation_strategy
+      .clone()
+      .or_else(|| Some(DEFAULT_SPOT_ALLOCATION_STRATEGY.to_string())),
+    availability_zone_number: request.availability_zone_number.or(Some(DEFAULT_AVAILABILITY_ZONE_NUMBER)),
+    ..request.clone()
+  }
+}
+
+/// Fields of the proposed request that differ from the current one
+fn field_changes(current: Option<&NodegroupRequestDto>, proposed: &NodegroupRequestDto) -> Vec<FieldChangeDto> {
+  let current = current.map(|current| json!(current));
+  match json!(proposed) {
+    Value::Object(proposed) => proposed
+      .into_iter()
+      .filter(|(field, _)| field != "name" && field != "skip_pull_request")
+      .filter_map(|(field, proposed)| {
+        let current = current.as_ref().and_then(|current| current.get(&field)).cloned();
+        let unchanged = match &current {
+          Some(current) => *current == proposed,
+          None => proposed.is_null(),
+        };
+        match unchanged {
+          true => None,
+          false => Some(FieldChangeDto { field, current, proposed }),
+        }
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use async_trait::async_trait;
+  use git2::Repository as GitRepository;
+  use serde_json::{json, Value};
+  use std::path::Path;
+  use tempfile::TempDir;
+
+  use crate::domain::errors::DomainError;
+  use crate::domain::model::{
+    CommitDto, GitOpsConfig, ManifestLayout, NewPullRequest, NodeGroupDto, NodegroupRequestDto, NodegroupUpdateDto, PullRequestDto,
+    PullRequestState, RequestContext, Workspace, DEFAULT_CATALOG_PATH,
+  };
+  use crate::domain::ports::incoming::NodegroupService;
+  use crate::domain::ports::outgoing::{PullRequestProvider, Repository, VersionControl};
+  use crate::domain::services::kubernetes::nodegroups::updated_request;
+  use crate::domain::services::kubernetes::validation::tests::{request, InstanceTypesStoreMock};
+  use crate::domain::services::{DefaultChangeRequestTemplates, DefaultNodegroupsService, DefaultTemplateService};
+  use crate::infrastructure::version_control::libgit2::tests::{bare_remote, config};
+  use crate::infrastructure::Git2VersionControl;
+
+  struct NodegroupsRepositoryMock(Option<NodeGroupDto>);
+
+  impl Repository<NodeGroupDto> for NodegroupsRepositoryMock {
+    fn find_by(&self, name: &str) -> Option<NodeGroupDto> {
+      self.0.clone().filter(|nodegroup| nodegroup.name == name)
+    }
+
+    fn find_all(&self) -> Option<Vec<NodeGroupDto>> {
+      Some(self.0.clone().into_iter().collect())
+    }
+  }
+
+  /// Fails on every call, to check that the GitOps repository is not touched
+  struct UnreachableVersionControl;
+
+  #[async_trait(?Send)]
+  impl VersionControl for UnreachableVersionControl {
+    async fn clone_repo(&self) -> Result<Workspace> {
+      unreachable!("clone_repo")
+    }
+
+    async fn auto_commit(&self, _workspace: &Workspace, _message: String) -> Result<CommitDto> {
+      unreachable!("auto_commit")
+    }
+
+    async fn push_branch(&self, _workspace: &Workspace, _commit_msg: String, _branch_name: String) -> Result<CommitDto> {
+      unreachable!("push_branch")
+    }
+  }
+
+  struct UnreachablePullRequestProvider;
+
+  #[async_trait(?Send)]
+  impl PullRequestProvider for UnreachablePullRequestProvider {
+    async fn create_pull_request(&self, _pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+      unreachable!("create_pull_request")
+    }
+
+    async fn pull_request_state(&self, _number: u64) -> Result<PullRequestState> {
+      unreachable!("pull_request_state")
+    }
+  }
+
+  struct PullRequestProviderMock;
+
+  #[async_trait(?Send)]
+  impl PullRequestProvider for PullRequestProviderMock {
+    async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+      Ok(PullRequestDto {
+        number: 1,
+        url: format!("https://github.com/test/gitops/pull/{}", pull_request.source_branch),
       })
-      .and_then(|gitops_path| match request.skip_pull_request {
-        true => self
-          .gitops_service
-          .auto_commit(gitops_path, "Directly commited a nodegroup from Rust back-end".into()),
-        false => self.gitops_service.pull_request(
-          gitops_path,
-          "First commit message from RUST".into(),
-          "Pr Title from Rust".into(),
-          "Body of the PR".into(),
-          "Test_branch_from_rust".into(),
-        ),
+    }
+
+    async fn pull_request_state(&self, _number: u64) -> Result<PullRequestState> {
+      Ok(PullRequestState::Open)
+    }
+  }
+
+  fn service() -> impl NodegroupService<NodeGroupDto> {
+    service_with(None)
+  }
+
+  fn service_with(live_nodegroup: Option<NodeGroupDto>) -> impl NodegroupService<NodeGroupDto> {
+    DefaultNodegroupsService::new(
+      NodegroupsRepositoryMock(live_nodegroup),
+      InstanceTypesStoreMock,
+      DefaultTemplateService::new().unwrap(),
+      UnreachableVersionControl,
+      UnreachablePullRequestProvider,
+      DefaultChangeRequestTemplates::new("templates/change_requests").unwrap(),
+      ManifestLayout::in_catalog("default", DEFAULT_CATALOG_PATH),
+    )
+  }
+
+  fn current() -> NodeGroupDto {
+    NodeGroupDto {
+      name: "batch-pool".to_string(),
+      enabled: Some(true),
+      availability_zone_number: Some(1),
+      instance_name: Some("m5.large".to_string()),
+      alternate_instance_names: vec!["m5a.large".to_string(), "m6a.large".to_string()],
+      min_size: Some(1),
+      max_size: Some(5),
+      target_size: Some(2),
+      ephemeral: Some("20Gi".to_string()),
+      lifecycle: Some("ondemand".to_string()),
+      spot_allocation_strategy: Some("capacity-optimized".to_string()),
+      labels: Default::default(),
+      taints: vec![],
+      state: Some("Present".to_string()),
+      conditions: vec![],
+      creation_timestamp: None,
+      last_update_time: None,
+      resource_version: Some("1".to_string()),
+      instance_type: None,
+      change_request: None,
+    }
+  }
+
+  fn update() -> NodegroupUpdateDto {
+    NodegroupUpdateDto {
+      default_instance_type: None,
+      alternate_instance_types: None,
+      min_size: None,
+      max_size: None,
+      target_size: None,
+      ephemeral: None,
+      lifecycle: None,
+      spot_allocation_strategy: None,
+      availability_zone_number: None,
+      labels: None,
+      taints: None,
+      skip_pull_request: false,
+    }
+  }
+
+  #[test]
+  fn keep_the_current_spec_for_missing_fields() {
+    let resize = NodegroupUpdateDto {
+      max_size: Some(10),
+      target_size: Some(8),
+      ..update()
+    };
+
+    let request = updated_request(&current(), &resize).unwrap();
+
+    assert_eq!(request.name, "batch-pool");
+    assert_eq!(request.default_instance_type, "m5.large");
+    assert_eq!(request.alternate_instance_types, vec!["m5a.large", "m6a.large"]);
+    assert_eq!(
+      (request.min_size, request.max_size, request.target_size),
+      (Some(1), Some(10), Some(8))
+    );
+    assert_eq!(request.ephemeral.as_str(), "20Gi");
+    assert_eq!(request.lifecycle.as_deref(), Some("ondemand"));
+    assert_eq!(request.spot_allocation_strategy.as_deref(), Some("capacity-optimized"));
+    assert_eq!(request.availability_zone_number, Some(1));
+  }
+
+  #[test]
+  fn require_the_values_missing_in_the_current_spec() {
+    let current = NodeGroupDto {
+      instance_name: None,
+      ephemeral: None,
+      ..current()
+    };
+
+    assert!(matches!(updated_request(&current, &update()), Err(DomainError::Validation(_))));
+    let complete = NodegroupUpdateDto {
+      default_instance_type: Some("m5.large".to_string()),
+      ephemeral: Some("10Gi".parse().unwrap()),
+      ..update()
+    };
+    assert!(updated_request(&current, &complete).is_ok());
+  }
+
+  #[test]
+  fn render_without_touching_the_repository() {
+    let manifest = service().render(&request()).unwrap();
+
+    assert!(manifest.contains("name: batch-pool"));
+    assert!(manifest.contains("alternates: [m5a.large]"));
+    let manifest: Value = serde_yaml::from_str(&manifest).unwrap();
+    assert_eq!(manifest["spec"]["kubernetes"]["labels"], json!({ "team": "data" }));
+    assert_eq!(
+      manifest["spec"]["kubernetes"]["taints"],
+      json!([{ "key": "dedicated", "effect": "NoSchedule" }])
+    );
+  }
+
+  #[test]
+  fn render_validates_the_request() {
+    let invalid_request = NodegroupRequestDto {
+      default_instance_type: "unknown".to_string(),
+      ..request()
+    };
+
+    assert!(matches!(service().render(&invalid_request), Err(DomainError::Validation(_))));
+  }
+
+  #[test]
+  fn diff_against_the_live_nodegroup() {
+    let resize = NodegroupUpdateDto {
+      max_size: Some(10),
+      ..update()
+    };
+    let request = updated_request(&current(), &resize).unwrap();
+
+    let diff = service_with(Some(current())).diff(&request).unwrap();
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].field, "max_size");
+    assert_eq!(diff.changes[0].current, Some(json!(5)));
+    assert_eq!(diff.changes[0].proposed, json!(10));
+    assert!(diff.unified_diff.contains("--- current\n+++ proposed\n"));
+    assert!(diff.unified_diff.contains("\n-    max: 5\n+    max: 10\n"));
+  }
+
+  #[test]
+  fn diff_a_new_nodegroup() {
+    let diff = service().diff(&request()).unwrap();
+
+    assert!(diff.changes.iter().all(|change| change.current.is_none()));
+    assert!(diff.changes.iter().any(|change| change.field == "default_instance_type"));
+    assert!(diff.unified_diff.contains("+  name: batch-pool\n"));
+  }
+
+  #[test]
+  fn isolate_parallel_creations() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let workspaces = folder.path().join("workspaces");
+    let git = Git2VersionControl::new(GitOpsConfig {
+      max_concurrent_operations: 2,
+      ..config(&remote_path, &workspaces)
+    });
+
+    let creations = (0..6)
+      .map(|index| {
+        let git = git.clone();
+        std::thread::spawn(move || {
+          let service = DefaultNodegroupsService::new(
+            NodegroupsRepositoryMock(None),
+            InstanceTypesStoreMock,
+            DefaultTemplateService::new().unwrap(),
+            git,
+            PullRequestProviderMock,
+            DefaultChangeRequestTemplates::new("templates/change_requests").unwrap(),
+            ManifestLayout::in_catalog("default", DEFAULT_CATALOG_PATH),
+          );
+          let request = NodegroupRequestDto {
+            name: format!("pool-{}", index),
+            ..request()
+          };
+          // Each creation runs in its own runtime, like in the job workers
+          let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+          runtime.block_on(service.create(&request, &RequestContext::default()))
+        })
       })
+      .collect::<Vec<_>>();
+    let change_requests = creations
+      .into_iter()
+      .map(|creation| creation.join().unwrap().unwrap())
+      .collect::<Vec<_>>();
+
+    let remote = GitRepository::open_bare(&remote_path).unwrap();
+    for (index, change_request) in change_requests.iter().enumerate() {
+      let tree = remote
+        .find_reference(&format!("refs/heads/{}", change_request.branch))
+        .and_then(|reference| reference.peel_to_tree())
+        .unwrap();
+      let catalog = tree
+        .get_path(Path::new(DEFAULT_CATALOG_PATH))
+        .and_then(|entry| entry.to_object(&remote))
+        .and_then(|object| object.peel_to_tree())
+        .unwrap();
+      let manifests = catalog
+        .iter()
+        .filter_map(|entry| entry.name().map(String::from))
+        .collect::<Vec<_>>();
+      assert_eq!(manifests, vec![format!("nodegroup-pool-{}.yaml", index)]);
+    }
+    assert_eq!(std::fs::read_dir(&workspaces).unwrap().count(), 0);
   }
 }
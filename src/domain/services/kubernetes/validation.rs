@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+
+use crate::domain::errors::{DomainError, FieldError};
+use crate::domain::model::{
+  InstanceType, NodegroupRequestDto, SealedSecretDto, SealingPolicy, SecretRequestDto, SecretType, SecretUpdateDto,
+};
+use crate::domain::ports::outgoing::ReadStore;
+use crate::utils::validators::dns::{is_dns1123_label, is_dns1123_subdomain, is_label_value, is_qualified_name};
+use crate::utils::validators::secrets::{is_matching_key_pair, is_pem_private_key, is_secret_key, is_valid_pem_certificate};
+
+const LIFECYCLES: [&str; 2] = ["ondemand", "spot"];
+const SPOT_ALLOCATION_STRATEGIES: [&str; 4] = [
+  "lowest-price",
+  "capacity-optimized",
+  "capacity-optimized-prioritized",
+  "price-capacity-optimized",
+];
+const TAINT_EFFECTS: [&str; 3] = ["NoSchedule", "PreferNoSchedule", "NoExecute"];
+const QUALIFIED_NAME_MESSAGE: &str =
+  "must be up to 63 alphanumeric characters, '-', '_' or '.', optionally prefixed by a DNS-1123 subdomain and '/'";
+const LABEL_VALUE_MESSAGE: &str =
+  "must be empty or up to 63 alphanumeric characters, '-', '_' or '.', starting and ending with an alphanumeric one";
+
+/// Validates a nodegroup request, collecting every field error found
+///
+/// # Arguments
+///
+/// * `request` - The nodegroup request to validate
+/// * `store` - Catalog of the available instance types
+///
+pub fn validate_nodegroup_request<S>(request: &NodegroupRequestDto, store: &S) -> Result<(), DomainError>
+where
+  S: ReadStore<InstanceType>,
+{
+  let mut errors = Vec::new();
+
+  if !is_dns1123_label(&request.name) {
+    errors.push(FieldError::new(
+      "name",
+      "must be a valid DNS-1123 label: lowercase alphanumeric characters or '-', up to 63 characters",
+    ));
+  }
+
+  if store.get(&request.default_instance_type).is_err() {
+    errors.push(FieldError::new(
+      "default_instance_type",
+      format!("unknown instance type {}", request.default_instance_type),
+    ));
+  }
+
+  for (index, alternate_instance_type) in request.alternate_instance_types.iter().enumerate() {
+    if store.get(alternate_instance_type).is_err() {
+      errors.push(FieldError::new(
+        format!("alternate_instance_types[{}]", index),
+        format!("unknown instance type {}", alternate_instance_type),
+      ));
+    }
+  }
+
+  match (request.min_size, request.max_size, request.target_size) {
+    (Some(min_size), Some(max_size), Some(target_size)) => {
+      if max_size == 0 {
+        errors.push(FieldError::new("max_size", "must be greater than 0"));
+      } else if min_size > max_size {
+        errors.push(FieldError::new("min_size", "must be lower or equal than max_size"));
+      } else if target_size < min_size || target_size > max_size {
+        errors.push(FieldError::new("target_size", "must be between min_size and max_size"));
+      }
+    }
+    (min_size, max_size, target_size) => {
+      for (field, size) in [("min_size", min_size), ("max_size", max_size), ("target_size", target_size)] {
+        if size.is_none() {
+          errors.push(FieldError::new(field, "is required"));
+        }
+      }
+    }
+  }
+
+  if let Some(lifecycle) = &request.lifecycle {
+    if !LIFECYCLES.contains(&lifecycle.as_str()) {
+      errors.push(FieldError::new("lifecycle", format!("must be one of {}", LIFECYCLES.join(", "))));
+    }
+  }
+
+  if let Some(strategy) = &request.spot_allocation_strategy {
+    if !SPOT_ALLOCATION_STRATEGIES.contains(&strategy.as_str()) {
+      errors.push(FieldError::new(
+        "spot_allocation_strategy",
+        format!("must be one of {}", SPOT_ALLOCATION_STRATEGIES.join(", ")),
+      ));
+    }
+  }
+
+  if request.availability_zone_number == Some(0) {
+    errors.push(FieldError::new("availability_zone_number", "must be greater than 0"));
+  }
+
+  // Checked like the API server does, not to commit a manifest rejected once applied
+  for (key, value) in &request.labels {
+    if !is_qualified_name(key) {
+      errors.push(FieldError::new(format!("labels[{}]", key), QUALIFIED_NAME_MESSAGE));
+    } else if !is_label_value(value) {
+      errors.push(FieldError::new(format!("labels[{}]", key), LABEL_VALUE_MESSAGE));
+    }
+  }
+
+  for (index, taint) in request.taints.iter().enumerate() {
+    if !is_qualified_name(&taint.key) {
+      errors.push(FieldError::new(format!("taints[{}].key", index), QUALIFIED_NAME_MESSAGE));
+    }
+    if !taint.value.iter().all(is_label_value) {
+      errors.push(FieldError::new(format!("taints[{}].value", index), LABEL_VALUE_MESSAGE));
+    }
+    if !TAINT_EFFECTS.contains(&taint.effect.as_str()) {
+      errors.push(FieldError::new(
+        format!("taints[{}].effect", index),
+        format!("must be one of {}", TAINT_EFFECTS.join(", ")),
+      ));
+    }
+  }
+
+  match errors.is_empty() {
+    true => Ok(()),
+    false => Err(DomainError::Validation(errors)),
+  }
+}
+
+/// Validates a secret request, collecting every field error found: its name, its namespace against the namespaces
+/// allowed in the cluster, and its values against its type
+///
+/// # Arguments
+///
+/// * `request` - The secret request to validate
+/// * `policy` - Sealing defaults and allowed namespaces of the cluster
+///
+pub fn validate_secret_request(request: &SecretRequestDto, policy: &SealingPolicy) -> Result<(), DomainError> {
+  let mut errors = Vec::new();
+
+  if !is_dns1123_subdomain(&request.name) {
+    errors.push(FieldError::new(
+      "name",
+      "must be a valid DNS-1123 subdomain: lowercase alphanumeric characters, '-' or '.', up to 253 characters",
+    ));
+  }
+  validate_namespace(&request.namespace, policy, &mut errors);
+
+  // Values the type of the secret is built from, the other ones must be left empty
+  let values = [
+    ("literals", !request.literals.is_empty(), request.secret_type == SecretType::Opaque),
+    ("files", !request.files.is_empty(), request.secret_type == SecretType::Opaque),
+    (
+      "docker_registry",
+      request.docker_registry.is_some(),
+      request.secret_type == SecretType::DockerRegistry,
+    ),
+    ("tls", request.tls.is_some(), request.secret_type == SecretType::Tls),
+  ];
+  for (field, _, _) in values.iter().filter(|(_, present, allowed)| *present && !*allowed) {
+    errors.push(FieldError::new(
+      *field,
+      format!("isn't allowed for {} secrets", request.secret_type),
+    ));
+  }
+
+  match request.secret_type {
+    SecretType::Opaque => {
+      if request.literals.is_empty() && request.files.is_empty() {
+        errors.push(FieldError::new("literals", "at least one literal or file is required"));
+      }
+      validate_opaque_values(&request.literals, &request.files, &mut errors)
+    }
+    SecretType::DockerRegistry => match &request.docker_registry {
+      Some(registry) => {
+        for (field, value) in [
+          ("server", &registry.server),
+          ("username", &registry.username),
+          ("password", &registry.password),
+        ] {
+          if value.trim().is_empty() {
+            errors.push(FieldError::new(format!("docker_registry.{}", field), "can not be empty"));
+          }
+        }
+      }
+      None => errors.push(FieldError::new("docker_registry", "is required for docker-registry secrets")),
+    },
+    SecretType::Tls => match &request.tls {
+      Some(tls) => validate_tls_pair("tls.certificate", &tls.certificate, "tls.key", &tls.key, &mut errors),
+      None => errors.push(FieldError::new("tls", "is required for tls secrets")),
+    },
+  }
+
+  match errors.is_empty() {
+    true => Ok(()),
+    false => Err(DomainError::Validation(errors)),
+  }
+}
+
+/// Validates an update of the keys of a sealed secret: its namespace against the namespaces allowed in the cluster,
+/// and the keys to set or remove
+///
+/// # Arguments
+///
+/// * `update` - The keys to add, rotate or remove
+/// * `policy` - Sealing defaults and allowed namespaces of the cluster
+///
+pub fn validate_secret_update(update: &SecretUpdateDto, policy: &SealingPolicy) -> Result<(), DomainError> {
+  let mut errors = Vec::new();
+  validate_namespace(&update.namespace, policy, &mut errors);
+
+  if update.literals.is_empty() && update.files.is_empty() && update.remove.is_empty() {
+    errors.push(FieldError::new(
+      "literals",
+      "at least one literal, file or key to remove is required",
+    ));
+  }
+  validate_opaque_values(&update.literals, &update.files, &mut errors);
+  for (index, key) in update.remove.iter().enumerate() {
+    if !is_secret_key(key) {
+      errors.push(FieldError::new(
+        format!("remove[{}]", index),
+        "must contain only alphanumeric characters, '-', '_' or '.'",
+      ));
+    } else if update.literals.contains_key(key) || update.files.contains_key(key) {
+      errors.push(FieldError::new(format!("remove[{}]", index), "is also set by the update"));
+    }
+  }
+
+  match errors.is_empty() {
+    true => Ok(()),
+    false => Err(DomainError::Validation(errors)),
+  }
+}
+
+/// Validates the changes against the sealed secret found in the repository: the removed keys must exist, the secret can't
+/// be left empty, the keys its type requires can't be removed, and the TLS certificates are replaced with their key.
+pub fn validate_secret_changes(update: &SecretUpdateDto, secret: &SealedSecretDto) -> Result<(), DomainError> {
+  let mut errors = Vec::new();
+  let required = match secret.secret_type.as_str() {
+    "kubernetes.io/dockerconfigjson" => vec![".dockerconfigjson"],
+    "kubernetes.io/tls" => vec!["tls.crt", "tls.key"],
+    _ => vec![],
+  };
+  for (index, key) in update.remove.iter().enumerate() {
+    if !secret.keys.contains(key) {
+      errors.push(FieldError::new(
+        format!("remove[{}]", index),
+        format!("isn't a key of the secret {}", secret.name),
+      ));
+    } else if required.contains(&key.as_str()) {
+      errors.push(FieldError::new(
+        format!("remove[{}]", index),
+        format!("is required by {} secrets", secret.secret_type),
+      ));
+    }
+  }
+  let remaining = secret.keys.iter().filter(|key| !update.remove.contains(key)).count();
+  if errors.is_empty() && remaining == 0 && update.literals.is_empty() && update.files.is_empty() {
+    errors.push(FieldError::new("remove", "can not remove every key of the secret"));
+  }
+
+  // The sealed half of the pair can't be read back, so the certificate and its key are checked and replaced together
+  if secret.secret_type == "kubernetes.io/tls" {
+    match (updated_value(update, "tls.crt"), updated_value(update, "tls.key")) {
+      (None, None) => {}
+      (Some((certificate_field, certificate)), Some((key_field, key))) => {
+        validate_tls_pair(&certificate_field, &certificate, &key_field, &key, &mut errors)
+      }
+      (Some((field, _)), None) | (None, Some((field, _))) => {
+        errors.push(FieldError::new(field, "tls.crt and tls.key must be replaced together"))
+      }
+    }
+  }
+
+  match errors.is_empty() {
+    true => Ok(()),
+    false => Err(DomainError::Validation(errors)),
+  }
+}
+
+/// Field and text value of a key set by the update, from its literals or its base64 encoded files
+fn updated_value(update: &SecretUpdateDto, key: &str) -> Option<(String, String)> {
+  let literal = update.literals.get(key).map(|value| (format!("literals.{}", key), value.clone()));
+  literal.or_else(|| {
+    update.files.get(key).map(|value| {
+      let text = base64::decode(value).ok().and_then(|value| String::from_utf8(value).ok());
+      (format!("files.{}", key), text.unwrap_or_default())
+    })
+  })
+}
+
+fn validate_tls_pair(certificate_field: &str, certificate: &str, key_field: &str, key: &str, errors: &mut Vec<FieldError>) {
+  let valid_certificate = is_valid_pem_certificate(certificate);
+  let valid_key = is_pem_private_key(key);
+  if !valid_certificate {
+    errors.push(FieldError::new(certificate_field, "must be an unexpired PEM certificate"));
+  }
+  if !valid_key {
+    errors.push(FieldError::new(key_field, "must be a PEM private key"));
+  } else if valid_certificate && !is_matching_key_pair(certificate, key) {
+    errors.push(FieldError::new(key_field, "doesn't match the certificate"));
+  }
+}
+
+fn validate_namespace(namespace: &Option<String>, policy: &SealingPolicy, errors: &mut Vec<FieldError>) {
+  if let Some(namespace) = namespace {
+    if !is_dns1123_label(namespace) {
+      errors.push(FieldError::new(
+        "namespace",
+        "must be a valid DNS-1123 label: lowercase alphanumeric characters or '-', up to 63 characters",
+      ));
+    } else if !policy.allowed_namespaces.contains(namespace) {
+      errors.push(FieldError::new(
+        "namespace",
+        format!("must be one of {}", policy.allowed_namespaces.join(", ")),
+      ));
+    }
+  }
+}
+
+fn validate_opaque_values(literals: &HashMap<String, String>, files: &HashMap<String, String>, errors: &mut Vec<FieldError>) {
+  let mut keys = literals.keys().map(|key| ("literals", key)).collect::<Vec<_>>();
+  keys.extend(files.keys().map(|key| ("files", key)));
+  keys.sort();
+  for (field, key) in keys {
+    if !is_secret_key(key) {
+      errors.push(FieldError::new(
+        format!("{}.{}", field, key),
+        "must contain only alphanumeric characters, '-', '_' or '.'",
+      ));
+    }
+    if field == "files" && literals.contains_key(key) {
+      errors.push(FieldError::new(format!("files.{}", key), "is also a literal"));
+    }
+    if field == "files" && base64::decode(&files[key]).is_err() {
+      errors.push(FieldError::new(format!("files.{}", key), "must be base64 encoded"));
+    }
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use anyhow::{anyhow, Result};
+  use openssl::pkey::{PKey, Private};
+  use std::collections::{BTreeMap, HashMap};
+
+  use crate::domain::errors::DomainError;
+  use crate::domain::model::secrets::{DockerRegistryDto, TlsDto};
+  use crate::domain::model::{
+    InstanceType, NodegroupRequestDto, SealedSecretDto, SealingPolicy, SealingScope, SecretRequestDto, SecretType, SecretUpdateDto,
+    TaintDto,
+  };
+  use crate::domain::ports::outgoing::ReadStore;
+  use crate::domain::services::kubernetes::validation::{
+    validate_nodegroup_request, validate_secret_changes, validate_secret_request, validate_secret_update,
+  };
+  use crate::infrastructure::sealed_secrets::client::tests::controller_keys;
+
+  pub struct InstanceTypesStoreMock;
+
+  impl ReadStore<InstanceType> for InstanceTypesStoreMock {
+    fn get<S: AsRef<str>>(&self, name: S) -> Result<InstanceType> {
+      match name.as_ref() {
+        "m5.large" | "m5a.large" | "m6a.large" => Ok(InstanceType {
+          name: name.as_ref().to_string(),
+          family: "General purpose".to_string(),
+          memory: 8589934592,
+          vcpu: 2,
+          gpu: 0,
+        }),
+        _ => Err(anyhow!("Key not found: {}", name.as_ref())),
+      }
+    }
+
+    fn list(&self) -> Result<Vec<InstanceType>> {
+      Ok(vec![])
+    }
+  }
+
+  pub fn request() -> NodegroupRequestDto {
+    NodegroupRequestDto {
+      name: "batch-pool".to_string(),
+      default_instance_type: "m5.large".to_string(),
+      alternate_instance_types: vec!["m5a.large".to_string()],
+      min_size: Some(1),
+      max_size: Some(5),
+      target_size: Some(2),
+      ephemeral: "20Gi".parse().unwrap(),
+      lifecycle: Some("spot".to_string()),
+      spot_allocation_strategy: Some("capacity-optimized".to_string()),
+      availability_zone_number: Some(3),
+      labels: BTreeMap::from([("team".to_string(), "data".to_string())]),
+      taints: vec![taint("dedicated", "NoSchedule")],
+      skip_pull_request: false,
+    }
+  }
+
+  fn taint(key: &str, effect: &str) -> TaintDto {
+    TaintDto {
+      key: key.to_string(),
+      value: None,
+      effect: effect.to_string(),
+    }
+  }
+
+  fn invalid_fields(request: &NodegroupRequestDto) -> Vec<String> {
+    match validate_nodegroup_request(request, &InstanceTypesStoreMock) {
+      Err(DomainError::Validation(errors)) => errors.into_iter().map(|error| error.field).collect(),
+      _ => vec![],
+    }
+  }
+
+  #[test]
+  fn accept_a_valid_request() {
+    assert!(validate_nodegroup_request(&request(), &InstanceTypesStoreMock).is_ok());
+  }
+
+  #[test]
+  fn report_every_invalid_field() {
+    let request = NodegroupRequestDto {
+      name: "Batch_Pool".to_string(),
+      default_instance_type: "m5.larg".to_string(),
+      alternate_instance_types: vec!["unknown".to_string()],
+      min_size: Some(6),
+      ..request()
+    };
+
+    assert_eq!(
+      invalid_fields(&request),
+      vec!["name", "default_instance_type", "alternate_instance_types[0]", "min_size"]
+    );
+  }
+
+  #[test]
+  fn reject_inconsistent_sizes() {
+    let min_greater_than_max = NodegroupRequestDto {
+      min_size: Some(6),
+      ..request()
+    };
+    let target_out_of_range = NodegroupRequestDto {
+      target_size: Some(10),
+      ..request()
+    };
+
+    let empty = NodegroupRequestDto {
+      min_size: Some(0),
+      max_size: Some(0),
+      target_size: Some(0),
+      ..request()
+    };
+    let missing_sizes = NodegroupRequestDto {
+      min_size: None,
+      max_size: None,
+      target_size: None,
+      ..request()
+    };
+
+    assert_eq!(invalid_fields(&min_greater_than_max), vec!["min_size"]);
+    assert_eq!(invalid_fields(&target_out_of_range), vec!["target_size"]);
+    assert_eq!(invalid_fields(&empty), vec!["max_size"]);
+    assert_eq!(invalid_fields(&missing_sizes), vec!["min_size", "max_size", "target_size"]);
+  }
+
+  #[test]
+  fn reject_unknown_lifecycles_and_taint_effects() {
+    let request = NodegroupRequestDto {
+      lifecycle: Some("reserved".to_string()),
+      spot_allocation_strategy: Some("cheapest".to_string()),
+      availability_zone_number: Some(0),
+      taints: vec![taint("dedicated", "NoSchedule"), taint("", "Evict")],
+      ..request()
+    };
+
+    assert_eq!(
+      invalid_fields(&request),
+      vec![
+        "lifecycle",
+        "spot_allocation_strategy",
+        "availability_zone_number",
+        "taints[1].key",
+        "taints[1].effect"
+      ]
+    );
+  }
+
+  #[test]
+  fn reject_the_labels_and_taints_refused_by_kubernetes() {
+    let request = NodegroupRequestDto {
+      labels: BTreeMap::from([
+        ("node.kubernetes.io/team".to_string(), "data".to_string()),
+        ("foo bar".to_string(), "data".to_string()),
+        ("a/b/c".to_string(), "data".to_string()),
+        ("team".to_string(), "x".repeat(64)),
+      ]),
+      taints: vec![
+        TaintDto {
+          value: Some("gpu_only".to_string()),
+          ..taint("example.com/dedicated", "NoSchedule")
+        },
+        TaintDto {
+          value: Some("not allowed".to_string()),
+          ..taint("dedicated pool", "NoSchedule")
+        },
+      ],
+      ..request()
+    };
+
+    assert_eq!(
+      invalid_fields(&request),
+      vec![
+        "labels[a/b/c]",
+        "labels[foo bar]",
+        "labels[team]",
+        "taints[1].key",
+        "taints[1].value"
+      ]
+    );
+  }
+
+  fn policy() -> SealingPolicy {
+    SealingPolicy {
+      namespace: "default".to_string(),
+      scope: SealingScope::Strict,
+      allowed_namespaces: vec!["default".to_string(), "payments".to_string()],
+    }
+  }
+
+  fn secret_request() -> SecretRequestDto {
+    SecretRequestDto {
+      name: "db-credentials".to_string(),
+      secret_type: SecretType::Opaque,
+      literals: HashMap::from([("password".to_string(), "s3cr3t".to_string())]),
+      files: HashMap::new(),
+      docker_registry: None,
+      tls: None,
+      skip_pull_request: false,
+      namespace: None,
+      scope: None,
+    }
+  }
+
+  fn invalid_secret_fields(request: &SecretRequestDto) -> Vec<String> {
+    match validate_secret_request(request, &policy()) {
+      Err(DomainError::Validation(errors)) => errors.into_iter().map(|error| error.field).collect(),
+      _ => vec![],
+    }
+  }
+
+  #[test]
+  fn accept_the_allowed_namespaces_only() {
+    let secret = |namespace: &str| SecretRequestDto {
+      namespace: Some(namespace.to_string()),
+      ..secret_request()
+    };
+
+    assert!(validate_secret_request(&secret_request(), &policy()).is_ok());
+    assert!(validate_secret_request(&secret("payments"), &policy()).is_ok());
+    for namespace in ["kube-system", "Payments", "../default"] {
+      assert_eq!(
+        invalid_secret_fields(&secret(namespace)),
+        vec!["namespace"],
+        "{} shouldn't be allowed",
+        namespace
+      );
+    }
+  }
+
+  #[test]
+  fn check_the_secret_names() {
+    let secret = |name: &str| SecretRequestDto {
+      name: name.to_string(),
+      ..secret_request()
+    };
+
+    assert!(validate_secret_request(&secret("tls.example.com"), &policy()).is_ok());
+    for name in ["DB-credentials", "db_credentials", "../db-credentials", ""] {
+      assert_eq!(invalid_secret_fields(&secret(name)), vec!["name"], "{} shouldn't be allowed", name);
+    }
+  }
+
+  #[test]
+  fn validate_the_opaque_values() {
+    let files = SecretRequestDto {
+      files: HashMap::from([("keystore.jks".to_string(), "/u3+7QAAAAI=".to_string())]),
+      ..secret_request()
+    };
+    assert!(validate_secret_request(&files, &policy()).is_ok());
+
+    let invalid = SecretRequestDto {
+      literals: HashMap::from([
+        ("api key".to_string(), "value".to_string()),
+        ("password".to_string(), "value".to_string()),
+      ]),
+      files: HashMap::from([
+        ("password".to_string(), "czNjcjN0".to_string()),
+        ("id_rsa".to_string(), "not base64!".to_string()),
+      ]),
+      ..secret_request()
+    };
+    assert_eq!(
+      invalid_secret_fields(&invalid),
+      vec!["files.id_rsa", "files.password", "literals.api key"]
+    );
+
+    let empty = SecretRequestDto {
+      literals: HashMap::new(),
+      ..secret_request()
+    };
+    assert_eq!(invalid_secret_fields(&empty), vec!["literals"]);
+  }
+
+  #[test]
+  fn validate_the_docker_registry_and_tls_values() {
+    let registry = SecretRequestDto {
+      secret_type: SecretType::DockerRegistry,
+      literals: HashMap::new(),
+      docker_registry: Some(DockerRegistryDto {
+        server: "ghcr.io".to_string(),
+        username: "robot".to_string(),
+        password: "".to_string(),
+        email: None,
+      }),
+      ..secret_request()
+    };
+    assert_eq!(invalid_secret_fields(&registry), vec!["docker_registry.password"]);
+    let missing = SecretRequestDto {
+      secret_type: SecretType::DockerRegistry,
+      ..secret_request()
+    };
+    assert_eq!(invalid_secret_fields(&missing), vec!["literals", "docker_registry"]);
+
+    let (key, certificate) = controller_keys();
+    let pem_key = |key: &PKey<Private>| String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+    let tls = |key: String| SecretRequestDto {
+      secret_type: SecretType::Tls,
+      literals: HashMap::new(),
+      tls: Some(TlsDto {
+        certificate: String::from_utf8(certificate.clone()).unwrap(),
+        key,
+      }),
+      ..secret_request()
+    };
+    assert!(validate_secret_request(&tls(pem_key(&key)), &policy()).is_ok());
+    assert_eq!(invalid_secret_fields(&tls(pem_key(&controller_keys().0))), vec!["tls.key"]);
+    assert_eq!(invalid_secret_fields(&tls("not a key".to_string())), vec!["tls.key"]);
+  }
+
+  #[test]
+  fn validate_the_updated_and_removed_keys() {
+    let update = SecretUpdateDto {
+      literals: HashMap::from([("password".to_string(), "n3w".to_string())]),
+      remove: vec!["legacy-token".to_string()],
+      ..SecretUpdateDto::default()
+    };
+    assert!(validate_secret_update(&update, &policy()).is_ok());
+
+    let invalid = SecretUpdateDto {
+      files: HashMap::from([("id_rsa".to_string(), "not base64!".to_string())]),
+      remove: vec!["password".to_string(), "id_rsa".to_string(), "../key".to_string()],
+      namespace: Some("kube-system".to_string()),
+      ..update.clone()
+    };
+    let errors: Vec<String> = match validate_secret_update(&invalid, &policy()) {
+      Err(DomainError::Validation(errors)) => errors.into_iter().map(|error| error.field).collect(),
+      _ => vec![],
+    };
+    assert_eq!(errors, vec!["namespace", "files.id_rsa", "remove[0]", "remove[1]", "remove[2]"]);
+    assert!(validate_secret_update(&SecretUpdateDto::default(), &policy()).is_err());
+
+    let secret = SealedSecretDto {
+      name: "registry".to_string(),
+      namespace: "default".to_string(),
+      scope: SealingScope::Strict,
+      secret_type: "kubernetes.io/dockerconfigjson".to_string(),
+      keys: vec![".dockerconfigjson".to_string(), "legacy-token".to_string()],
+    };
+    assert!(validate_secret_changes(&update, &secret).is_ok());
+    let removed = |keys: &[&str]| SecretUpdateDto {
+      remove: keys.iter().map(|key| key.to_string()).collect(),
+      ..SecretUpdateDto::default()
+    };
+    assert!(validate_secret_changes(&removed(&["unknown"]), &secret).is_err());
+    assert!(validate_secret_changes(&removed(&[".dockerconfigjson"]), &secret).is_err());
+    let opaque = SealedSecretDto {
+      secret_type: "Opaque".to_string(),
+      ..secret
+    };
+    let errors: Vec<String> = match validate_secret_changes(&removed(&[".dockerconfigjson", "legacy-token"]), &opaque) {
+      Err(DomainError::Validation(errors)) => errors.into_iter().map(|error| error.field).collect(),
+      _ => vec![],
+    };
+    assert_eq!(errors, vec!["remove"]);
+  }
+
+  #[test]
+  fn replace_the_tls_certificates_with_their_key() {
+    let secret = SealedSecretDto {
+      name: "ingress-tls".to_string(),
+      namespace: "default".to_string(),
+      scope: SealingScope::Strict,
+      secret_type: "kubernetes.io/tls".to_string(),
+      keys: vec!["tls.crt".to_string(), "tls.key".to_string()],
+    };
+    let (key, certificate) = controller_keys();
+    let certificate = String::from_utf8(certificate).unwrap();
+    let pem_key = |key: &PKey<Private>| String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+    let update = |values: &[(&str, String)]| SecretUpdateDto {
+      literals: values.iter().map(|(key, value)| (key.to_string(), value.clone())).collect(),
+      ..SecretUpdateDto::default()
+    };
+    let invalid_fields = |update: &SecretUpdateDto| -> Vec<String> {
+      match validate_secret_changes(update, &secret) {
+        Err(DomainError::Validation(errors)) => errors.into_iter().map(|error| error.field).collect(),
+        _ => vec![],
+      }
+    };
+
+    let rotated = update(&[("tls.crt", certificate.clone()), ("tls.key", pem_key(&key))]);
+    assert!(validate_secret_changes(&rotated, &secret).is_ok());
+    assert_eq!(
+      invalid_fields(&update(&[("tls.crt", certificate.clone())])),
+      vec!["literals.tls.crt"]
+    );
+    assert_eq!(invalid_fields(&update(&[("tls.key", pem_key(&key))])), vec!["literals.tls.key"]);
+    let mismatch = update(&[("tls.crt", certificate), ("tls.key", pem_key(&controller_keys().0))]);
+    assert_eq!(invalid_fields(&mismatch), vec!["literals.tls.key"]);
+  }
+}
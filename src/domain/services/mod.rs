@@ -1,11 +1,17 @@
+pub mod change_requests;
 pub mod instance_types;
+pub mod jobs;
 pub mod kubernetes;
 pub mod probes;
+pub mod reconciler;
 pub mod templates;
 
+pub use change_requests::DefaultChangeRequestTemplates;
 pub use instance_types::cron_service::ScheduledInstanceTypesService;
 pub use instance_types::reader_service::DefaultInstanceTypesService;
 pub use instance_types::updater::DefaultInstanceTypesUpdater;
+pub use jobs::DefaultJobsService;
 pub use kubernetes::nodegroups::DefaultNodegroupsService;
-pub use kubernetes::secrets::DefaultSecretsService;
+pub use kubernetes::secrets::{DefaultSecretsService, SecretSealing};
+pub use reconciler::DefaultChangeRequestReconciler;
 pub use templates::DefaultTemplateService;
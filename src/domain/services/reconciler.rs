@@ -0,0 +1,486 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use tokio::time::{self, Duration};
+
+use crate::domain::errors::DomainError;
+use crate::domain::model::{
+  ChangeRequestDto, ChangeRequestMode, ChangeRequestState, NodeGroupDto, PullRequestState, SecretDto, TrackedChangeDto,
+};
+use crate::domain::ports::incoming::{ChangeRequestTracker, ScheduledService};
+use crate::domain::ports::outgoing::{ChangeRequestStore, NamespacedRepository, PullRequestProvider, Repository};
+use crate::utils::time::now;
+
+/// Interval between two reconciliations in seconds
+const RECONCILE_INTERVAL_SECS: u64 = 60;
+
+/// Time given to the GitOps controller (ArgoCD) to apply a merged change before reporting it as failed, in seconds
+const APPLY_TIMEOUT_SECS: u64 = 60 * 60; // One hour
+
+/// Follows the change requests submitted through the API until the cluster reflects them.
+///
+/// The Pull Requests are polled through the PullRequestProvider until they are merged or closed. Once in the base branch,
+/// a change is applied when the reflectors see a new version of the resource (or no resource at all, for deletions).
+pub struct DefaultChangeRequestReconciler<N, S, P, T>
+where
+  N: Repository<NodeGroupDto>,
+  S: Repository<SecretDto> + NamespacedRepository<SecretDto>,
+  P: PullRequestProvider,
+  T: ChangeRequestStore,
+{
+  nodegroups_repository: N,
+  secrets_repository: S,
+  pull_request_provider: P,
+  store: T,
+}
+
+impl<N, S, P, T> DefaultChangeRequestReconciler<N, S, P, T>
+where
+  N: Repository<NodeGroupDto>,
+  S: Repository<SecretDto> + NamespacedRepository<SecretDto>,
+  P: PullRequestProvider,
+  T: ChangeRequestStore,
+{
+  pub fn new(nodegroups_repository: N, secrets_repository: S, pull_request_provider: P, store: T) -> Self {
+    Self {
+      nodegroups_repository,
+      secrets_repository,
+      pull_request_provider,
+      store,
+    }
+  }
+
+  /// Version of the resource seen in the cluster, or None if it doesn't exist. The secrets of a given namespace are
+  /// looked up in the cluster, the other ones in the reflectors.
+  async fn resource_version(&self, change: &TrackedChangeDto) -> Result<Option<String>> {
+    Ok(match (change.kind.as_str(), &change.namespace) {
+      ("nodegroup", _) => self
+        .nodegroups_repository
+        .find_by(&change.name)
+        .map(|nodegroup| nodegroup.resource_version.unwrap_or_default()),
+      ("secret", Some(namespace)) => self
+        .secrets_repository
+        .find_in(namespace, &change.name)
+        .await?
+        .map(|secret| secret.version),
+      ("secret", None) => self.secrets_repository.find_by(&change.name).map(|secret| secret.version),
+      _ => None,
+    })
+  }
+
+  /// Computes the next state of a change in progress. Returns None if it didn't change.
+  async fn advance(&self, change: &TrackedChangeDto) -> Result<Option<TrackedChangeDto>> {
+    match change.state {
+      ChangeRequestState::PendingPullRequest => {
+        let number = match change.change_request.pull_request_number {
+          Some(number) => number,
+          None => return Ok(Some(merged(change))),
+        };
+        Ok(match self.pull_request_provider.pull_request_state(number).await? {
+          PullRequestState::Open => None,
+          PullRequestState::Merged => Some(merged(change)),
+          PullRequestState::Closed => Some(failed(change, "The pull request was closed without being merged")),
+        })
+      }
+      ChangeRequestState::Merged => {
+        let version = self.resource_version(change).await?;
+        let applied = match change.action.as_str() {
+          "delete" => version.is_none(),
+          _ => version.is_some() && version != change.observed_version,
+        };
+        let merged_at = change.merged_at.unwrap_or(change.submitted_at);
+        Ok(if applied {
+          Some(TrackedChangeDto {
+            state: ChangeRequestState::Applied,
+            updated_at: now(),
+            ..change.clone()
+          })
+        } else if now().saturating_sub(merged_at) > APPLY_TIMEOUT_SECS {
+          Some(failed(
+            change,
+            &format!("Not applied in the cluster {} minutes after the merge", APPLY_TIMEOUT_SECS / 60),
+          ))
+        } else {
+          None
+        })
+      }
+      ChangeRequestState::Applied | ChangeRequestState::Failed => Ok(None),
+    }
+  }
+}
+
+/// Marks the change as merged. The version of the resource to be replaced was observed when the change was tracked.
+fn merged(change: &TrackedChangeDto) -> TrackedChangeDto {
+  let now = now();
+  TrackedChangeDto {
+    state: ChangeRequestState::Merged,
+    merged_at: Some(now),
+    updated_at: now,
+    ..change.clone()
+  }
+}
+
+fn failed(change: &TrackedChangeDto, reason: &str) -> TrackedChangeDto {
+  TrackedChangeDto {
+    state: ChangeRequestState::Failed,
+    reason: Some(reason.to_string()),
+    updated_at: now(),
+    ..change.clone()
+  }
+}
+
+#[async_trait(?Send)]
+impl<N, S, P, T> ChangeRequestTracker for DefaultChangeRequestReconciler<N, S, P, T>
+where
+  N: Repository<NodeGroupDto>,
+  S: Repository<SecretDto> + NamespacedRepository<SecretDto>,
+  P: PullRequestProvider,
+  T: ChangeRequestStore,
+{
+  async fn track(&self, kind: &str, name: &str, namespace: Option<&str>, action: &str, change_request: &ChangeRequestDto) {
+    let now = now();
+    let change = TrackedChangeDto {
+      kind: kind.to_string(),
+      name: name.to_string(),
+      namespace: namespace.map(String::from),
+      action: action.to_string(),
+      change_request: change_request.clone(),
+      state: ChangeRequestState::PendingPullRequest,
+      reason: None,
+      observed_version: None,
+      merged_at: None,
+      submitted_at: now,
+      updated_at: now,
+    };
+    // The version to be replaced is observed right away: the GitOps controller may apply the change before its merge is polled
+    let change = self.resource_version(&change).await.map(|observed_version| {
+      let change = TrackedChangeDto {
+        observed_version,
+        ..change
+      };
+      match change_request.mode {
+        ChangeRequestMode::DirectCommit => merged(&change),
+        ChangeRequestMode::PullRequest => change,
+      }
+    });
+    if let Err(err) = change.and_then(|change| self.store.save(&change)) {
+      error!("Error tracking the change request of the {} {}: {}", kind, name, err);
+    }
+  }
+
+  fn status(&self, kind: &str, name: &str, namespace: Option<&str>) -> Option<TrackedChangeDto> {
+    self.store.find(kind, name, namespace).unwrap_or_else(|err| {
+      error!("Error reading the change request of the {} {}: {}", kind, name, err);
+      None
+    })
+  }
+
+  async fn reconcile(&self) -> Result<usize, DomainError> {
+    let changes = self.store.list().map_err(DomainError::Store)?;
+    let mut updated = 0;
+    for change in changes {
+      match self.advance(&change).await {
+        Ok(Some(next)) => {
+          info!(
+            "Change request of the {} {}: {:?} -> {:?}",
+            next.kind, next.name, change.state, next.state
+          );
+          self.store.save(&next).map_err(DomainError::Store)?;
+          updated += 1;
+        }
+        Ok(None) => {}
+        Err(err) => warn!(
+          "Error reconciling the change request of the {} {}: {}",
+          change.kind, change.name, err
+        ),
+      }
+    }
+    Ok(updated)
+  }
+}
+
+impl<N, S, P, T> ScheduledService for DefaultChangeRequestReconciler<N, S, P, T>
+where
+  N: Repository<NodeGroupDto> + 'static,
+  S: Repository<SecretDto> + NamespacedRepository<SecretDto> + 'static,
+  P: PullRequestProvider + Send + 'static,
+  T: ChangeRequestStore + Send + 'static,
+{
+  fn start(self) -> Result<()> {
+    // The provider calls are not Send, so the reconciler runs in its own single-threaded runtime
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    std::thread::Builder::new().name("reconciler".to_string()).spawn(move || {
+      runtime.block_on(async move {
+        let mut interval = time::interval(Duration::from_secs(RECONCILE_INTERVAL_SECS));
+        loop {
+          interval.tick().await;
+          if let Err(err) = self.reconcile().await {
+            error!("Error reconciling the change requests: {}", err);
+          }
+        }
+      })
+    })?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use async_trait::async_trait;
+  use std::collections::HashMap;
+  use std::sync::{Arc, Mutex};
+
+  use crate::domain::model::{
+    ChangeRequestDto, ChangeRequestState, CommitDto, NewPullRequest, NodeGroupDto, PullRequestDto, PullRequestState, SecretDto,
+    TrackedChangeDto,
+  };
+  use crate::domain::ports::incoming::ChangeRequestTracker;
+  use crate::domain::ports::outgoing::{ChangeRequestStore, NamespacedRepository, PullRequestProvider, Repository};
+  use crate::domain::services::reconciler::DefaultChangeRequestReconciler;
+
+  #[derive(Clone, Default)]
+  struct NodegroupsRepositoryMock(Arc<Mutex<Option<NodeGroupDto>>>);
+
+  impl NodegroupsRepositoryMock {
+    fn set(&self, resource_version: Option<&str>) {
+      *self.0.lock().unwrap() = resource_version.map(|resource_version| NodeGroupDto {
+        name: "batch-pool".to_string(),
+        resource_version: Some(resource_version.to_string()),
+        ..Default::default()
+      });
+    }
+  }
+
+  impl Repository<NodeGroupDto> for NodegroupsRepositoryMock {
+    fn find_by(&self, name: &str) -> Option<NodeGroupDto> {
+      self.0.lock().unwrap().clone().filter(|nodegroup| nodegroup.name == name)
+    }
+
+    fn find_all(&self) -> Option<Vec<NodeGroupDto>> {
+      Some(self.0.lock().unwrap().clone().into_iter().collect())
+    }
+  }
+
+  /// Secrets of the cluster, by namespace and name, with their version
+  #[derive(Clone, Default)]
+  struct SecretsRepositoryMock(Arc<Mutex<HashMap<(String, String), String>>>);
+
+  impl SecretsRepositoryMock {
+    fn set(&self, namespace: &str, name: &str, version: &str) {
+      let key = (namespace.to_string(), name.to_string());
+      self.0.lock().unwrap().insert(key, version.to_string());
+    }
+  }
+
+  impl Repository<SecretDto> for SecretsRepositoryMock {
+    fn find_by(&self, _name: &str) -> Option<SecretDto> {
+      None
+    }
+
+    fn find_all(&self) -> Option<Vec<SecretDto>> {
+      Some(vec![])
+    }
+  }
+
+  #[async_trait(?Send)]
+  impl NamespacedRepository<SecretDto> for SecretsRepositoryMock {
+    async fn find_in(&self, namespace: &str, name: &str) -> Result<Option<SecretDto>> {
+      let key = (namespace.to_string(), name.to_string());
+      Ok(self.0.lock().unwrap().get(&key).map(|version| SecretDto {
+        name: name.to_string(),
+        namespace: namespace.to_string(),
+        version: version.clone(),
+      }))
+    }
+  }
+
+  /// Fake git provider where the tests decide the state of the Pull Requests
+  #[derive(Clone)]
+  struct PullRequestProviderFake(Arc<Mutex<PullRequestState>>);
+
+  impl PullRequestProviderFake {
+    fn set(&self, state: PullRequestState) {
+      *self.0.lock().unwrap() = state;
+    }
+  }
+
+  #[async_trait(?Send)]
+  impl PullRequestProvider for PullRequestProviderFake {
+    async fn create_pull_request(&self, _pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+      unreachable!("create_pull_request")
+    }
+
+    async fn pull_request_state(&self, _number: u64) -> Result<PullRequestState> {
+      Ok(*self.0.lock().unwrap())
+    }
+  }
+
+  #[derive(Default)]
+  struct ChangeRequestStoreMock(Mutex<HashMap<String, TrackedChangeDto>>);
+
+  impl ChangeRequestStore for ChangeRequestStoreMock {
+    fn save(&self, change: &TrackedChangeDto) -> Result<()> {
+      let key = TrackedChangeDto::key(&change.kind, &change.name, change.namespace.as_deref());
+      self.0.lock().unwrap().insert(key, change.clone());
+      Ok(())
+    }
+
+    fn find(&self, kind: &str, name: &str, namespace: Option<&str>) -> Result<Option<TrackedChangeDto>> {
+      Ok(self.0.lock().unwrap().get(&TrackedChangeDto::key(kind, name, namespace)).cloned())
+    }
+
+    fn list(&self) -> Result<Vec<TrackedChangeDto>> {
+      Ok(self.0.lock().unwrap().values().cloned().collect())
+    }
+  }
+
+  fn commit() -> CommitDto {
+    CommitDto {
+      branch: "create-nodegroup-batch-pool-1".to_string(),
+      sha: "abc".to_string(),
+    }
+  }
+
+  fn pull_request() -> ChangeRequestDto {
+    ChangeRequestDto::pull_request(
+      commit(),
+      PullRequestDto {
+        number: 42,
+        url: "https://github.com/test/gitops/pull/42".to_string(),
+      },
+    )
+  }
+
+  fn reconciler(nodegroups: &NodegroupsRepositoryMock, provider: &PullRequestProviderFake) -> impl ChangeRequestTracker {
+    reconciler_with(nodegroups, &SecretsRepositoryMock::default(), provider)
+  }
+
+  fn reconciler_with(
+    nodegroups: &NodegroupsRepositoryMock,
+    secrets: &SecretsRepositoryMock,
+    provider: &PullRequestProviderFake,
+  ) -> impl ChangeRequestTracker {
+    DefaultChangeRequestReconciler::new(
+      nodegroups.clone(),
+      secrets.clone(),
+      provider.clone(),
+      ChangeRequestStoreMock::default(),
+    )
+  }
+
+  fn state<R: ChangeRequestTracker>(reconciler: &R) -> ChangeRequestState {
+    reconciler.status("nodegroup", "batch-pool", None).unwrap().state
+  }
+
+  #[tokio::test]
+  async fn follow_a_pull_request_until_the_change_is_applied() {
+    let nodegroups = NodegroupsRepositoryMock::default();
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Open)));
+    let reconciler = reconciler(&nodegroups, &provider);
+
+    reconciler.track("nodegroup", "batch-pool", None, "create", &pull_request()).await;
+    assert_eq!(reconciler.reconcile().await.unwrap(), 0);
+    assert_eq!(state(&reconciler), ChangeRequestState::PendingPullRequest);
+
+    provider.set(PullRequestState::Merged);
+    assert_eq!(reconciler.reconcile().await.unwrap(), 1);
+    assert_eq!(state(&reconciler), ChangeRequestState::Merged);
+
+    nodegroups.set(Some("1"));
+    assert_eq!(reconciler.reconcile().await.unwrap(), 1);
+    assert_eq!(state(&reconciler), ChangeRequestState::Applied);
+    assert_eq!(reconciler.reconcile().await.unwrap(), 0);
+  }
+
+  #[tokio::test]
+  async fn fail_when_the_pull_request_is_closed() {
+    let nodegroups = NodegroupsRepositoryMock::default();
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Closed)));
+    let reconciler = reconciler(&nodegroups, &provider);
+
+    reconciler.track("nodegroup", "batch-pool", None, "update", &pull_request()).await;
+    reconciler.reconcile().await.unwrap();
+
+    let change = reconciler.status("nodegroup", "batch-pool", None).unwrap();
+    assert_eq!(change.state, ChangeRequestState::Failed);
+    assert!(change.reason.unwrap().contains("closed"));
+  }
+
+  #[tokio::test]
+  async fn wait_for_a_new_version_of_the_updated_resources() {
+    let nodegroups = NodegroupsRepositoryMock::default();
+    nodegroups.set(Some("1"));
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Open)));
+    let reconciler = reconciler(&nodegroups, &provider);
+
+    let direct_commit = ChangeRequestDto::direct_commit(commit());
+    reconciler.track("nodegroup", "batch-pool", None, "update", &direct_commit).await;
+    assert_eq!(state(&reconciler), ChangeRequestState::Merged);
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Merged);
+
+    nodegroups.set(Some("2"));
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Applied);
+  }
+
+  #[tokio::test]
+  async fn apply_the_updates_synced_before_the_merge_is_polled() {
+    let nodegroups = NodegroupsRepositoryMock::default();
+    nodegroups.set(Some("1"));
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Open)));
+    let reconciler = reconciler(&nodegroups, &provider);
+
+    reconciler.track("nodegroup", "batch-pool", None, "update", &pull_request()).await;
+    provider.set(PullRequestState::Merged);
+    nodegroups.set(Some("2"));
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Merged);
+
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Applied);
+  }
+
+  #[tokio::test]
+  async fn apply_the_deletions_when_the_resource_is_gone() {
+    let nodegroups = NodegroupsRepositoryMock::default();
+    nodegroups.set(Some("1"));
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Open)));
+    let reconciler = reconciler(&nodegroups, &provider);
+
+    let direct_commit = ChangeRequestDto::direct_commit(commit());
+    reconciler.track("nodegroup", "batch-pool", None, "delete", &direct_commit).await;
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Merged);
+
+    nodegroups.set(None);
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state(&reconciler), ChangeRequestState::Applied);
+  }
+
+  #[tokio::test]
+  async fn follow_the_secrets_in_their_namespace() {
+    let secrets = SecretsRepositoryMock::default();
+    secrets.set("default", "db-credentials", "1");
+    let provider = PullRequestProviderFake(Arc::new(Mutex::new(PullRequestState::Open)));
+    let reconciler = reconciler_with(&NodegroupsRepositoryMock::default(), &secrets, &provider);
+    let state = |namespace| reconciler.status("secret", "db-credentials", Some(namespace)).unwrap().state;
+
+    let direct_commit = ChangeRequestDto::direct_commit(commit());
+    reconciler
+      .track("secret", "db-credentials", Some("payments"), "create", &direct_commit)
+      .await;
+    reconciler
+      .track("secret", "db-credentials", Some("default"), "update", &direct_commit)
+      .await;
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state("payments"), ChangeRequestState::Merged);
+    assert_eq!(state("default"), ChangeRequestState::Merged);
+
+    secrets.set("payments", "db-credentials", "7");
+    reconciler.reconcile().await.unwrap();
+    assert_eq!(state("payments"), ChangeRequestState::Applied);
+    assert_eq!(state("default"), ChangeRequestState::Merged);
+  }
+}
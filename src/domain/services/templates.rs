@@ -1,6 +1,6 @@
 use anyhow::{Error, Result};
-use handlebars::Handlebars;
-use std::collections::HashMap;
+use handlebars::{no_escape, Handlebars};
+use serde::Serialize;
 use std::fs::File;
 
 use crate::domain::ports::incoming::TemplateService;
@@ -8,6 +8,8 @@ use crate::domain::ports::incoming::TemplateService;
 #[derive(Clone)]
 pub struct DefaultTemplateService<'a> {
   pub handlebars: Box<Handlebars<'a>>,
+  /// Registry of the inline path templates: strict and without HTML escaping
+  paths: Box<Handlebars<'a>>,
 }
 
 impl<'a> DefaultTemplateService<'a> {
@@ -17,22 +19,97 @@ impl<'a> DefaultTemplateService<'a> {
     handlebars.set_strict_mode(false);
     handlebars.register_templates_directory(".yaml", "templates")?;
 
+    let mut paths = Handlebars::new();
+    paths.set_strict_mode(true);
+    paths.register_escape_fn(no_escape);
+
     Ok(Self {
       handlebars: Box::new(handlebars),
+      paths: Box::new(paths),
     })
   }
 }
 
 impl<'a> TemplateService for DefaultTemplateService<'a> {
-  fn render(&self, name: &str, values: &HashMap<&str, &str>) -> Result<String, Error> {
-    self.handlebars.render(name, &values).map_err(anyhow::Error::msg)
+  fn render<V: Serialize>(&self, name: &str, values: &V) -> Result<String, Error> {
+    self.handlebars.render(name, values).map_err(anyhow::Error::msg)
   }
 
-  fn write_to_file(&self, name: &str, values: &HashMap<&str, &str>, file_path: &str) -> Result<(), Error> {
+  fn write_to_file<V: Serialize>(&self, name: &str, values: &V, file_path: &str) -> Result<(), Error> {
     let output_file = File::create(file_path)?;
     self
       .handlebars
-      .render_to_write(name, &values, output_file)
+      .render_to_write(name, values, output_file)
       .map_err(anyhow::Error::msg)
   }
+
+  fn render_path<V: Serialize>(&self, template: &str, values: &V) -> Result<String, Error> {
+    self.paths.render_template(template, values).map_err(anyhow::Error::msg)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::domain::ports::incoming::TemplateService;
+  use crate::domain::services::DefaultTemplateService;
+
+  #[test]
+  fn render_the_nodegroup_template() {
+    let template_service = DefaultTemplateService::new().unwrap();
+    let mut values = json!({
+      "NAME": "batch-pool",
+      "EPHEMERAL_STORAGE": "20Gi",
+      "DEFAULT_INSTANCE_TYPE": "m5.large",
+      "ALTERNATE_INSTANCE_TYPES": "",
+      "MIN_SIZE": 1,
+      "MAX_SIZE": 5,
+      "TARGET_SIZE": 2,
+      "LIFECYCLE": "spot",
+      "SPOT_ALLOCATION_STRATEGY": "capacity-optimized",
+      "AVAILABILITY_ZONE_NUMBER": 3,
+      "LABELS": [{ "key": r#""team""#, "value": r#""data""# }],
+      "TAINTS": [{ "key": r#""dedicated""#, "value": r#""batch""#, "effect": r#""NoSchedule""# }],
+    });
+
+    let manifest = template_service.render("nodegroup", &values).unwrap();
+
+    assert!(manifest.contains("name: batch-pool"));
+    assert!(manifest.contains("min: 1\n    max: 5\n    target: 2"));
+    assert!(manifest.contains("ephemeral: 20Gi"));
+    assert!(!manifest.contains("alternates"));
+    assert!(manifest.contains("availabilityZoneNumber: 3"));
+    assert!(manifest.contains("lifecycle: spot\n  spotAllocationStrategy: capacity-optimized"));
+    assert!(manifest.contains(
+      r#"  kubernetes:
+    labels:
+      "team": "data"
+    taints:
+      - key: "dedicated"
+        value: "batch"
+        effect: "NoSchedule"
+"#
+    ));
+
+    values["LABELS"] = json!([]);
+    values["TAINTS"] = json!([]);
+    let manifest = template_service.render("nodegroup", &values).unwrap();
+    assert!(
+      manifest.ends_with("  kubernetes:\n    labels: {}\n    taints: []\n"),
+      "{}",
+      manifest
+    );
+  }
+
+  #[test]
+  fn render_the_paths_without_escaping() {
+    let template_service = DefaultTemplateService::new().unwrap();
+    let values = json!({"cluster": "staging", "name": "db-credentials&co"});
+
+    let path = template_service.render_path("clusters/{{cluster}}/secrets/{{name}}.yaml", &values);
+
+    assert_eq!(path.unwrap(), "clusters/staging/secrets/db-credentials&co.yaml");
+    assert!(template_service.render_path("{{namespace}}/{{name}}.yaml", &values).is_err());
+  }
 }
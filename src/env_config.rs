@@ -1,7 +1,10 @@
-use std::env;
+use serde_derive::Deserialize;
+use std::{env, fs};
 use thiserror::Error;
 
-use crate::domain::model::GitOpsConfig;
+use crate::domain::model::{
+  CertificateSource, ClusterConfig, ClusterRegistry, GitOpsConfig, GitProvider, ManifestLayout, SealingScope, DEFAULT_CATALOG_PATH,
+};
 
 type Result<T> = core::result::Result<T, EnvConfigError>;
 
@@ -9,6 +12,84 @@ type Result<T> = core::result::Result<T, EnvConfigError>;
 pub enum EnvConfigError {
   #[error("Missing environment variable: {0}")]
   MissingEnvVar(String),
+
+  #[error("Invalid value for the environment variable {0}: {1}")]
+  InvalidEnvVar(String, String),
+
+  #[error("Invalid configuration file {0}: {1}")]
+  InvalidConfigFile(String, String),
+
+  #[error("Invalid configuration of the cluster {0}: {1}")]
+  InvalidCluster(String, String),
+}
+
+/// Content of the clusters configuration file
+#[derive(Deserialize)]
+struct ClustersFile {
+  /// Cluster used when a request doesn't name any. The first one when missing
+  default_cluster: Option<String>,
+  clusters: Vec<ClusterEntry>,
+}
+
+/// Cluster of the configuration file. The optional settings default like their `GITPOS_*` counterparts.
+#[derive(Deserialize)]
+struct ClusterEntry {
+  id: String,
+  organization: String,
+  repository: String,
+  branch: String,
+  host: Option<String>,
+  provider: Option<String>,
+  repository_url: Option<String>,
+  api_url: Option<String>,
+  catalog_path: Option<String>,
+  /// Template of the nodegroup manifest paths. Defaults to `nodegroup-{{name}}.yaml` in the catalog
+  nodegroup_path: Option<String>,
+  /// Template of the sealed secret manifest paths. Defaults to `sealed-secret-{{name}}.yaml` in the catalog
+  secret_path: Option<String>,
+  kube_context: Option<String>,
+  namespace: Option<String>,
+  /// PEM file, URL, `service:{namespace}/{name}` or `secret:{namespace}` of the sealed-secrets controller.
+  /// Defaults to the `sealed-secrets-controller` service in `kube-system`
+  sealing_certificate: Option<String>,
+  /// `strict` (default), `namespace-wide` or `cluster-wide`
+  sealing_scope: Option<String>,
+  /// Namespaces the requests can seal secrets for, besides `namespace`
+  #[serde(default)]
+  sealing_namespaces: Vec<String>,
+}
+
+impl ClusterEntry {
+  /// Single cluster configured through the environment variables
+  fn from_env() -> Result<Self> {
+    Ok(Self {
+      id: EnvConfig::var("CLUSTER_ID").unwrap_or_else(|_| "default".to_string()),
+      organization: EnvConfig::var("GITPOS_ORGANIZATION")?,
+      repository: EnvConfig::var("GITPOS_REPO")?,
+      branch: EnvConfig::var("GITPOS_BRANCH")?,
+      host: EnvConfig::var("GITPOS_HOST").ok(),
+      provider: EnvConfig::var("GITPOS_PROVIDER").ok(),
+      repository_url: EnvConfig::var("GITPOS_REPO_URL").ok(),
+      api_url: EnvConfig::var("GITPOS_API_URL").ok(),
+      catalog_path: EnvConfig::var("GITPOS_CATALOG_PATH").ok(),
+      nodegroup_path: EnvConfig::var("GITPOS_NODEGROUP_PATH").ok(),
+      secret_path: EnvConfig::var("GITPOS_SECRET_PATH").ok(),
+      kube_context: EnvConfig::var("KUBE_CONTEXT").ok(),
+      namespace: None,
+      sealing_certificate: EnvConfig::var("SEALED_SECRETS_CERTIFICATE").ok(),
+      sealing_scope: EnvConfig::var("SEALED_SECRETS_SCOPE").ok(),
+      sealing_namespaces: EnvConfig::var("SEALED_SECRETS_NAMESPACES")
+        .map(|namespaces| {
+          namespaces
+            .split(',')
+            .map(str::trim)
+            .filter(|namespace| !namespace.is_empty())
+            .map(String::from)
+            .collect()
+        })
+        .unwrap_or_default(),
+    })
+  }
 }
 
 /// Helper providing application configuration injected through the environment variables
@@ -29,17 +110,107 @@ impl EnvConfig {
     Self::var("NAMESPACE")
   }
 
-  pub fn gitops_config() -> Result<GitOpsConfig> {
-    let organization = Self::var("GITPOS_ORGANIZATION")?;
-    let repository_name = Self::var("GITPOS_REPO")?;
-    let destination_folder = Self::var("GITPOS_DESTINATION_FOLDER")?;
-    let branch = Self::var("GITPOS_BRANCH")?;
+  /// Clusters managed by the API, read from the JSON file in `CLUSTERS_CONFIG_FILE`.
+  /// Without it, a single cluster is configured from the `GITPOS_*` environment variables.
+  pub fn cluster_registry() -> Result<ClusterRegistry> {
+    match Self::var("CLUSTERS_CONFIG_FILE") {
+      Ok(path) => {
+        let invalid_file = |err: String| EnvConfigError::InvalidConfigFile(path.clone(), err);
+        let content = fs::read_to_string(&path).map_err(|err| invalid_file(err.to_string()))?;
+        let file = serde_json::from_str::<ClustersFile>(&content).map_err(|err| invalid_file(err.to_string()))?;
+        let clusters = file.clusters.iter().map(Self::cluster_config).collect::<Result<Vec<_>>>()?;
+        ClusterRegistry::new(file.default_cluster, clusters).map_err(invalid_file)
+      }
+      Err(_) => {
+        let cluster = Self::cluster_config(&ClusterEntry::from_env()?)?;
+        let id = cluster.id.clone();
+        ClusterRegistry::new(None, vec![cluster]).map_err(|err| EnvConfigError::InvalidCluster(id, err))
+      }
+    }
+  }
+
+  fn cluster_config(cluster: &ClusterEntry) -> Result<ClusterConfig> {
+    let catalog_layout = ManifestLayout::in_catalog(&cluster.id, cluster.catalog_path.as_deref().unwrap_or(DEFAULT_CATALOG_PATH));
+    Ok(ClusterConfig {
+      id: cluster.id.clone(),
+      gitops: Self::gitops_config(cluster)?,
+      layout: ManifestLayout {
+        nodegroup: cluster.nodegroup_path.clone().unwrap_or(catalog_layout.nodegroup),
+        secret: cluster.secret_path.clone().unwrap_or(catalog_layout.secret),
+        ..catalog_layout
+      },
+      kube_context: cluster.kube_context.clone(),
+      namespace: match &cluster.namespace {
+        Some(namespace) => namespace.clone(),
+        None => Self::namespace()?,
+      },
+      sealing_certificate: match &cluster.sealing_certificate {
+        Some(source) => source
+          .parse::<CertificateSource>()
+          .map_err(|err| EnvConfigError::InvalidCluster(cluster.id.clone(), err))?,
+        None => CertificateSource::default(),
+      },
+      sealing_scope: match &cluster.sealing_scope {
+        Some(scope) => scope
+          .parse::<SealingScope>()
+          .map_err(|err| EnvConfigError::InvalidCluster(cluster.id.clone(), err))?,
+        None => SealingScope::Strict,
+      },
+      sealing_namespaces: cluster.sealing_namespaces.clone(),
+    })
+  }
+
+  /// Settings of the GitOps repository of a cluster. The credentials, the commit author and the local folders are shared
+  /// by all the clusters, each one getting its own sub-folder.
+  fn gitops_config(cluster: &ClusterEntry) -> Result<GitOpsConfig> {
+    let organization = cluster.organization.clone();
+    let repository_name = cluster.repository.clone();
+    let destination_folder = format!("{}/{}", Self::var("GITPOS_DESTINATION_FOLDER")?, cluster.id);
+    let mirror_folder = format!("{}/gitops/{}", Self::stores_path()?, cluster.id);
+    let branch = cluster.branch.clone();
+    let host = cluster.host.clone().unwrap_or_else(|| "github.com".to_string());
+    let repository_path = format!("{}/{}", organization, repository_name);
+    let repository_url = cluster
+      .repository_url
+      .clone()
+      .unwrap_or_else(|| format!("https://{}/{}.git", host, repository_path));
+    let username = Self::var("GITHUB_USER").ok();
+    let token = Self::var("GITHUB_TOKEN").ok();
+    let author_name = Self::var("GITPOS_AUTHOR_NAME")
+      .ok()
+      .or_else(|| username.clone())
+      .unwrap_or_else(|| "unicron-backend-api".to_string());
+    let author_email = Self::var("GITPOS_AUTHOR_EMAIL").unwrap_or_else(|_| format!("{}@users.noreply.{}", author_name, host));
+    let provider = match &cluster.provider {
+      Some(value) => value
+        .parse::<GitProvider>()
+        .map_err(|err| EnvConfigError::InvalidCluster(cluster.id.clone(), err))?,
+      None => GitProvider::GitHub,
+    };
+    let api_url = cluster.api_url.clone().unwrap_or_else(|| provider.api_url(&host));
+    let max_concurrent_operations = match Self::var("GITPOS_MAX_CONCURRENT_OPERATIONS") {
+      Ok(value) => value
+        .parse::<usize>()
+        .ok()
+        .filter(|max| *max > 0)
+        .ok_or_else(|| EnvConfigError::InvalidEnvVar("GITPOS_MAX_CONCURRENT_OPERATIONS".to_string(), value))?,
+      Err(_) => 4,
+    };
     Ok(GitOpsConfig {
-      repository_name: repository_name.clone(),
-      organization: organization.clone(),
+      repository_name,
+      organization,
       branch,
       destination_folder,
-      repository_path: format!("{}/{}", organization, repository_name),
+      mirror_folder,
+      repository_path,
+      repository_url,
+      username,
+      token,
+      author_name,
+      author_email,
+      provider,
+      api_url,
+      max_concurrent_operations,
     })
   }
 
@@ -47,6 +218,34 @@ impl EnvConfig {
     Self::var("STORES_PATH").or_else(|_| Ok(env::temp_dir().join("stores").to_string_lossy().into_owned()))
   }
 
+  /// Number of background workers running the GitOps jobs
+  pub fn job_workers() -> Result<usize> {
+    match Self::var("JOB_WORKERS") {
+      Ok(value) => value
+        .parse::<usize>()
+        .ok()
+        .filter(|workers| *workers > 0)
+        .ok_or_else(|| EnvConfigError::InvalidEnvVar("JOB_WORKERS".to_string(), value)),
+      Err(_) => Ok(4),
+    }
+  }
+
+  /// Seconds the certificates of the sealed-secrets controllers are cached before being loaded again
+  pub fn sealing_certificate_ttl() -> Result<u64> {
+    match Self::var("SEALED_SECRETS_CERTIFICATE_TTL") {
+      Ok(value) => value
+        .parse::<u64>()
+        .ok()
+        .filter(|ttl| *ttl > 0)
+        .ok_or_else(|| EnvConfigError::InvalidEnvVar("SEALED_SECRETS_CERTIFICATE_TTL".to_string(), value)),
+      Err(_) => Ok(3600),
+    }
+  }
+
+  pub fn change_request_templates_path() -> Result<String> {
+    Self::var("CHANGE_REQUEST_TEMPLATES_PATH").or_else(|_| Ok("templates/change_requests".to_string()))
+  }
+
   pub fn instance_types_file_source() -> Result<String> {
     Self::var("INSTANCE_TYPES_FILE_SOURCE").or_else(|err| {
       env::current_dir()
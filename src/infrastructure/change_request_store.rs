@@ -0,0 +1,49 @@
+use anyhow::Result;
+use kv::{Bincode, Bucket, Codec};
+
+use crate::domain::model::TrackedChangeDto;
+use crate::domain::ports::outgoing::ChangeRequestStore;
+
+const CHANGE_REQUESTS_BUCKET: &str = "change_requests";
+
+/// Change request store persisted in a bucket of the key-value store per cluster, keyed by `{kind}/{name}` or
+/// `{kind}/{namespace}/{name}`
+#[derive(Clone)]
+pub struct KvChangeRequestStore<'a> {
+  bucket: Bucket<'a, String, Bincode<TrackedChangeDto>>,
+}
+
+impl<'a> KvChangeRequestStore<'a> {
+  pub fn new(store: &kv::Store, cluster: &str) -> Result<Self> {
+    let bucket_name = format!("{}/{}", CHANGE_REQUESTS_BUCKET, cluster);
+    let bucket = store.bucket::<String, Bincode<TrackedChangeDto>>(Some(&bucket_name))?;
+    Ok(Self { bucket })
+  }
+}
+
+impl<'a> ChangeRequestStore for KvChangeRequestStore<'a> {
+  fn save(&self, change: &TrackedChangeDto) -> Result<()> {
+    let key = TrackedChangeDto::key(&change.kind, &change.name, change.namespace.as_deref());
+    self.bucket.set(key, Bincode(change.clone()))?;
+    self.bucket.flush()?;
+    Ok(())
+  }
+
+  fn find(&self, kind: &str, name: &str, namespace: Option<&str>) -> Result<Option<TrackedChangeDto>> {
+    Ok(self.bucket.get(TrackedChangeDto::key(kind, name, namespace))?.map(|bincode| bincode.into_inner()))
+  }
+
+  fn list(&self) -> Result<Vec<TrackedChangeDto>> {
+    Ok(
+      self
+        .bucket
+        .iter()
+        .filter_map(|maybe_item| {
+          maybe_item
+            .and_then(|item| item.value::<Bincode<TrackedChangeDto>>().map(|x| x.0))
+            .ok()
+        })
+        .collect(),
+    )
+  }
+}
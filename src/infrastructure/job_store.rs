@@ -0,0 +1,42 @@
+use anyhow::Result;
+use kv::{Bincode, Bucket, Codec};
+
+use crate::domain::model::JobDto;
+use crate::domain::ports::outgoing::JobStore;
+
+const JOBS_BUCKET: &str = "jobs";
+
+/// Job store persisted in its own bucket of the key-value store, keyed by the job id
+#[derive(Clone)]
+pub struct KvJobStore<'a> {
+  bucket: Bucket<'a, String, Bincode<JobDto>>,
+}
+
+impl<'a> KvJobStore<'a> {
+  pub fn new(store: &kv::Store) -> Result<Self> {
+    let bucket = store.bucket::<String, Bincode<JobDto>>(Some(JOBS_BUCKET))?;
+    Ok(Self { bucket })
+  }
+}
+
+impl<'a> JobStore for KvJobStore<'a> {
+  fn save(&self, job: &JobDto) -> Result<()> {
+    self.bucket.set(job.id.clone(), Bincode(job.clone()))?;
+    self.bucket.flush()?;
+    Ok(())
+  }
+
+  fn find(&self, id: &str) -> Result<Option<JobDto>> {
+    Ok(self.bucket.get(id)?.map(|bincode| bincode.into_inner()))
+  }
+
+  fn list(&self) -> Result<Vec<JobDto>> {
+    Ok(
+      self
+        .bucket
+        .iter()
+        .filter_map(|maybe_item| maybe_item.and_then(|item| item.value::<Bincode<JobDto>>().map(|x| x.0)).ok())
+        .collect(),
+    )
+  }
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{api::ListParams, client::Client, Api};
+
+use crate::domain::model::CertificateSource;
+use crate::infrastructure::sealed_secrets::CertificateLoader;
+
+/// Label of the key secrets of the controller, `active` for the ones used to seal
+const ACTIVE_KEY_LABEL: &str = "sealedsecrets.bitnami.com/sealed-secrets-key=active";
+
+/// Loads the certificate of the sealed-secrets controller of a cluster from its configured source
+#[derive(Clone)]
+pub struct ControllerCertificateLoader {
+  source: CertificateSource,
+  client: Client,
+  http_client: reqwest::Client,
+}
+
+impl ControllerCertificateLoader {
+  pub fn new(source: CertificateSource, client: Client) -> Self {
+    Self {
+      source,
+      client,
+      http_client: reqwest::Client::new(),
+    }
+  }
+
+  async fn fetch_url(&self, url: &str) -> Result<String> {
+    let response = self.http_client.get(url).send().await?.error_for_status()?;
+    Ok(response.text().await?)
+  }
+
+  /// Gets the certificate from the controller like `kubeseal` does, through the service proxy of the Kubernetes API
+  async fn fetch_service(&self, namespace: &str, name: &str) -> Result<String> {
+    let path = format!("/api/v1/namespaces/{}/services/http:{}:/proxy/v1/cert.pem", namespace, name);
+    let request = http::Request::get(path).body(vec![])?;
+    Ok(self.client.request_text(request).await?)
+  }
+
+  /// Reads the certificate of the newest active key, the one the controller seals with
+  async fn fetch_secret(&self, namespace: &str) -> Result<String> {
+    let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+    let keys = secrets.list(&ListParams::default().labels(ACTIVE_KEY_LABEL)).await?;
+    let newest = keys
+      .items
+      .into_iter()
+      .max_by_key(|secret| secret.metadata.creation_timestamp.clone())
+      .ok_or_else(|| anyhow!("No active sealed-secrets key in the namespace {}", namespace))?;
+    let certificate = newest
+      .data
+      .and_then(|mut data| data.remove("tls.crt"))
+      .ok_or_else(|| anyhow!("The sealed-secrets key {:?} has no tls.crt", newest.metadata.name))?;
+    String::from_utf8(certificate.0).context("The sealed-secrets certificate isn't PEM encoded")
+  }
+}
+
+#[async_trait(?Send)]
+impl CertificateLoader for ControllerCertificateLoader {
+  async fn load(&self) -> Result<String> {
+    let certificate = match &self.source {
+      CertificateSource::File(path) => std::fs::read_to_string(path).map_err(anyhow::Error::from),
+      CertificateSource::Url(url) => self.fetch_url(url).await,
+      CertificateSource::Service { namespace, name } => self.fetch_service(namespace, name).await,
+      CertificateSource::Secret { namespace } => self.fetch_secret(namespace).await,
+    };
+    certificate.with_context(|| format!("Error loading the sealed-secrets certificate from {}", self.source))
+  }
+}
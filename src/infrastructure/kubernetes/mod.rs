@@ -1,11 +1,11 @@
+pub mod certificate_loader;
 pub mod model;
 pub mod nodegroups_repository;
-pub mod sealed_secret_client;
 pub mod secrets_repository;
 pub mod transformations;
 
 pub use model::node_group_spec::NodeGroupSpec;
 
+pub use certificate_loader::ControllerCertificateLoader;
 pub use nodegroups_repository::DefaultNodegroupsRepository;
-pub use sealed_secret_client::KubesealClient;
 pub use secrets_repository::DefaultSecretsRepository;
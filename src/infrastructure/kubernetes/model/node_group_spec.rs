@@ -1,6 +1,7 @@
 use kube_derive::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(CustomResource, Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
 #[kube(
@@ -12,10 +13,31 @@ use serde::{Deserialize, Deserializer, Serialize};
 )]
 /// Representation of a Node Group spec
 pub struct NodeGroupSpec {
+  pub enabled: Option<bool>,
+  #[serde(alias = "availabilityZoneNumber")]
+  pub availability_zone_number: Option<usize>,
   pub size: Option<NodeGroupSize>,
   pub storage: Option<NodeGroupStorage>,
   #[serde(alias = "instanceTypes")]
   pub instance_types: Option<NodeGroupInstanceTypes>,
+  pub lifecycle: Option<String>,
+  #[serde(alias = "spotAllocationStrategy")]
+  pub spot_allocation_strategy: Option<String>,
+  pub kubernetes: Option<NodeGroupKubernetes>,
+}
+
+/// Labels and taints applied to the nodes of the group
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NodeGroupKubernetes {
+  pub labels: Option<BTreeMap<String, String>>,
+  pub taints: Option<Vec<NodeGroupTaint>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NodeGroupTaint {
+  pub key: String,
+  pub value: Option<String>,
+  pub effect: String,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,6 +61,20 @@ pub struct NodeGroupSize {
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct NodeGroupStatus {
   pub state: Option<NodeGroupState>,
+  pub conditions: Option<Vec<NodeGroupCondition>>,
+  #[serde(alias = "lastUpdateTime")]
+  pub last_update_time: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NodeGroupCondition {
+  #[serde(rename = "type")]
+  pub condition_type: String,
+  pub status: String,
+  pub reason: Option<String>,
+  pub message: Option<String>,
+  #[serde(alias = "lastTransitionTime")]
+  pub last_transition_time: Option<String>,
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq, JsonSchema)]
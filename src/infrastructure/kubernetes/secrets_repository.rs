@@ -10,7 +10,7 @@ use futures::StreamExt;
 use reflector::store::Writer;
 
 use crate::domain::model::SecretDto;
-use crate::domain::ports::outgoing::Repository;
+use crate::domain::ports::outgoing::{NamespacedRepository, Repository};
 use kube::{
   api::ListParams,
   client::Client,
@@ -29,11 +29,12 @@ pub struct DefaultSecretsRepository {
   //thread_handle: Option<JoinHandle<Result<()>>>,
   store: Store<Secret>,
   namespace: String,
+  client: Client,
 }
 
 impl DefaultSecretsRepository {
   pub fn new(client: Client, namespace: &str) -> Self {
-    let secrets: Api<Secret> = Api::namespaced(client, &*namespace);
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), &*namespace);
 
     let store = reflector::store::Writer::<Secret>::default();
     let reader = store.as_reader();
@@ -47,6 +48,7 @@ impl DefaultSecretsRepository {
       //thread_handle: Some(tokio::spawn(async {my_future.await})),
       store: reader,
       namespace: namespace.to_string(),
+      client,
     }
   }
 
@@ -75,6 +77,22 @@ impl Repository<SecretDto> for DefaultSecretsRepository {
   }
 }
 
+/// The reflector only watches the namespace of the cluster, the secrets of the other namespaces are read on demand
+#[async_trait(?Send)]
+impl NamespacedRepository<SecretDto> for DefaultSecretsRepository {
+  async fn find_in(&self, namespace: &str, name: &str) -> Result<Option<SecretDto>> {
+    if namespace == self.namespace {
+      return Ok(self.find_by(name));
+    }
+    let secrets: Api<Secret> = Api::namespaced(self.client.clone(), namespace);
+    match secrets.get(name).await {
+      Ok(secret) => Ok(SecretDto::try_from(secret).ok()),
+      Err(kube::Error::Api(response)) if response.code == 404 => Ok(None),
+      Err(err) => Err(err.into()),
+    }
+  }
+}
+
 /*
 
 let mut stream = watcher(deploys, ListParams::default()).boxed();
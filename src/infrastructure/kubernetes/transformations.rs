@@ -1,8 +1,9 @@
-use crate::domain::model::{NodeGroupDto, SecretDto};
+use crate::domain::model::{NodeGroupConditionDto, NodeGroupDto, SecretDto, TaintDto};
 use anyhow::Result;
 use k8s_openapi::api::core::v1::Secret;
 use std::convert::TryFrom;
 
+use crate::infrastructure::kubernetes::model::node_group_spec::{NodeGroupCondition, NodeGroupTaint};
 use crate::infrastructure::kubernetes::model::NodeGroup;
 use kube::ResourceExt;
 
@@ -15,7 +16,7 @@ impl TryFrom<Secret> for SecretDto {
     Ok(SecretDto {
       name,
       namespace,
-      version: "1".to_string(),
+      version: d.resource_version().unwrap_or_else(|| "1".to_string()),
     })
   }
 }
@@ -25,21 +26,144 @@ impl TryFrom<NodeGroup> for NodeGroupDto {
 
   fn try_from(ng: NodeGroup) -> Result<Self> {
     let name = ng.name();
-    let instance_name = ng.spec.instance_types.map(|instance_types| instance_types.default);
-    let ephemeral = ng.spec.storage.and_then(|storage| storage.ephemeral);
+    let creation_timestamp = ng.metadata.creation_timestamp.as_ref().map(|time| time.0.to_rfc3339());
+    let resource_version = ng.metadata.resource_version.clone();
+    let spec = ng.spec;
+    let instance_name = spec.instance_types.as_ref().map(|instance_types| instance_types.default.clone());
+    let alternate_instance_names = spec
+      .instance_types
+      .and_then(|instance_types| instance_types.alternates)
+      .unwrap_or_default();
+    let ephemeral = spec.storage.and_then(|storage| storage.ephemeral);
 
-    let min_size = ng.spec.size.as_ref().and_then(|size| size.min);
-    let max_size = ng.spec.size.as_ref().and_then(|size| size.max);
-    let state = ng.status.and_then(|status| status.state).map(|status| status.to_string());
+    let min_size = spec.size.as_ref().and_then(|size| size.min);
+    let max_size = spec.size.as_ref().and_then(|size| size.max);
+    let target_size = spec.size.as_ref().and_then(|size| size.target);
+
+    let (labels, taints) = spec
+      .kubernetes
+      .map(|kubernetes| (kubernetes.labels.unwrap_or_default(), kubernetes.taints.unwrap_or_default()))
+      .unwrap_or_default();
+    let taints = taints.into_iter().map(TaintDto::from).collect();
+
+    let status = ng.status;
+    let state = status
+      .as_ref()
+      .and_then(|status| status.state.as_ref())
+      .map(|state| state.to_string());
+    let last_update_time = status.as_ref().and_then(|status| status.last_update_time.clone());
+    let conditions = status
+      .and_then(|status| status.conditions)
+      .unwrap_or_default()
+      .into_iter()
+      .map(NodeGroupConditionDto::from)
+      .collect();
 
     Ok(NodeGroupDto {
       name,
+      enabled: spec.enabled,
+      availability_zone_number: spec.availability_zone_number,
       instance_name,
+      alternate_instance_names,
       min_size,
       max_size,
+      target_size,
       ephemeral,
+      lifecycle: spec.lifecycle,
+      spot_allocation_strategy: spec.spot_allocation_strategy,
+      labels,
+      taints,
       state,
+      conditions,
+      creation_timestamp,
+      last_update_time,
+      resource_version,
       instance_type: None,
+      change_request: None,
     })
   }
 }
+
+impl From<NodeGroupTaint> for TaintDto {
+  fn from(taint: NodeGroupTaint) -> Self {
+    TaintDto {
+      key: taint.key,
+      value: taint.value,
+      effect: taint.effect,
+    }
+  }
+}
+
+impl From<NodeGroupCondition> for NodeGroupConditionDto {
+  fn from(condition: NodeGroupCondition) -> Self {
+    NodeGroupConditionDto {
+      condition_type: condition.condition_type,
+      status: condition.status,
+      reason: condition.reason,
+      message: condition.message,
+      last_transition_time: condition.last_transition_time,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+  use std::convert::TryFrom;
+
+  use crate::domain::model::{NodeGroupDto, TaintDto};
+  use crate::infrastructure::kubernetes::model::NodeGroup;
+
+  #[test]
+  fn convert_the_full_nodegroup() {
+    let nodegroup: NodeGroup = serde_json::from_value(json!({
+      "apiVersion": "cluster.unicron.mpi-internal.com/v1alpha1",
+      "kind": "NodeGroup",
+      "metadata": { "name": "batch-pool", "creationTimestamp": "2021-10-01T10:00:00Z", "resourceVersion": "4242" },
+      "spec": {
+        "enabled": true,
+        "availabilityZoneNumber": 1,
+        "size": { "min": 1, "max": 5, "target": 2 },
+        "instanceTypes": { "default": "m5.large", "alternates": ["m5a.large"] },
+        "lifecycle": "spot",
+        "spotAllocationStrategy": "capacity-optimized",
+        "storage": { "ephemeral": "20Gi" },
+        "kubernetes": {
+          "labels": { "team": "data" },
+          "taints": [{ "key": "dedicated", "value": "batch", "effect": "NoSchedule" }]
+        }
+      },
+      "status": {
+        "state": "Present",
+        "lastUpdateTime": "2021-10-01T10:05:00Z",
+        "conditions": [{ "type": "Ready", "status": "True", "lastTransitionTime": "2021-10-01T10:05:00Z" }]
+      }
+    }))
+    .unwrap();
+
+    let dto = NodeGroupDto::try_from(nodegroup).unwrap();
+
+    assert_eq!(dto.name, "batch-pool");
+    assert_eq!(dto.creation_timestamp.as_deref(), Some("2021-10-01T10:00:00+00:00"));
+    assert_eq!(dto.resource_version.as_deref(), Some("4242"));
+    assert_eq!((dto.enabled, dto.availability_zone_number), (Some(true), Some(1)));
+    assert_eq!((dto.min_size, dto.max_size, dto.target_size), (Some(1), Some(5), Some(2)));
+    assert_eq!(dto.instance_name.as_deref(), Some("m5.large"));
+    assert_eq!(dto.alternate_instance_names, vec!["m5a.large"]);
+    assert_eq!(dto.lifecycle.as_deref(), Some("spot"));
+    assert_eq!(dto.spot_allocation_strategy.as_deref(), Some("capacity-optimized"));
+    assert_eq!(dto.labels.get("team").map(String::as_str), Some("data"));
+    assert_eq!(
+      dto.taints,
+      vec![TaintDto {
+        key: "dedicated".to_string(),
+        value: Some("batch".to_string()),
+        effect: "NoSchedule".to_string(),
+      }]
+    );
+    assert_eq!(dto.state.as_deref(), Some("Present"));
+    assert_eq!(dto.last_update_time.as_deref(), Some("2021-10-01T10:05:00Z"));
+    assert_eq!(dto.conditions[0].condition_type, "Ready");
+    assert_eq!(dto.conditions[0].last_transition_time.as_deref(), Some("2021-10-01T10:05:00Z"));
+  }
+}
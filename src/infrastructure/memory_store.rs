@@ -2,7 +2,7 @@ use anyhow::anyhow;
 use anyhow::{Error, Result};
 use kv::{Bincode, Bucket, Codec};
 use serde::Serialize;
-use std::{collections::HashSet, path::Path};
+use std::collections::HashSet;
 
 use crate::domain::ports::incoming::WithName;
 use crate::domain::ports::outgoing::{ReadStore, WriteStore};
@@ -19,9 +19,8 @@ impl<'a, T> InMemoryStore<'a, T>
 where
   T: Serialize + serde::de::DeserializeOwned + WithName,
 {
-  pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-    let config = kv::Config::new(path.as_ref());
-    let store = kv::Store::new(config)?;
+  /// Uses the default bucket of an already opened store, that can only be opened once per process
+  pub fn from_store(store: &kv::Store) -> Result<Self> {
     let bucket = store.bucket::<String, Bincode<T>>(None)?;
     Ok(Self { bucket })
   }
@@ -1,7 +1,15 @@
+pub mod change_request_store;
 pub mod datasources;
+pub mod job_store;
 pub mod kubernetes;
 pub mod memory_store;
+pub mod pull_requests;
+pub mod sealed_secrets;
 pub mod version_control;
 
+pub use change_request_store::KvChangeRequestStore;
+pub use job_store::KvJobStore;
 pub use memory_store::InMemoryStore;
-pub use version_control::git::GitVersionControl;
+pub use pull_requests::GitPullRequestProvider;
+pub use sealed_secrets::{CachedCertificateProvider, NativeSealedSecretClient};
+pub use version_control::Git2VersionControl;
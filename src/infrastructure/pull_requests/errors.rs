@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PullRequestError {
+  #[error("Error calling the git provider API: {0}")]
+  Request(#[source] reqwest::Error),
+
+  #[error("The git provider rejected the pull request with status {0}: {1}")]
+  Rejected(u16, String),
+}
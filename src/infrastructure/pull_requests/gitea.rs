@@ -0,0 +1,144 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::domain::model::{GitOpsConfig, NewPullRequest, PullRequestDto, PullRequestState};
+use crate::domain::ports::outgoing::PullRequestProvider;
+use crate::infrastructure::pull_requests::{http_client, send};
+
+/// Opens Pull Requests through the Gitea REST API
+#[derive(Clone)]
+pub struct GiteaPullRequestProvider {
+  client: Client,
+  api_url: String,
+  repository_path: String,
+  base_branch: String,
+  token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GiteaPullRequestBody<'a> {
+  title: &'a str,
+  body: &'a str,
+  head: &'a str,
+  base: &'a str,
+}
+
+#[derive(Deserialize)]
+struct GiteaPullRequestResponse {
+  number: u64,
+  html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaPullRequestStateResponse {
+  state: String,
+  merged: bool,
+}
+
+impl GiteaPullRequestProvider {
+  pub fn new(config: &GitOpsConfig) -> Self {
+    Self {
+      client: http_client(),
+      api_url: config.api_url.trim_end_matches('/').to_string(),
+      repository_path: config.repository_path.clone(),
+      base_branch: config.branch.clone(),
+      token: config.token.clone(),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl PullRequestProvider for GiteaPullRequestProvider {
+  async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+    let url = format!("{}/repos/{}/pulls", self.api_url, self.repository_path);
+    let mut request = self.client.post(url).json(&GiteaPullRequestBody {
+      title: &pull_request.title,
+      body: &pull_request.body,
+      head: &pull_request.source_branch,
+      base: &self.base_branch,
+    });
+    if let Some(token) = &self.token {
+      request = request.header("Authorization", format!("token {}", token));
+    }
+
+    let response: GiteaPullRequestResponse = send(request).await?;
+    Ok(PullRequestDto {
+      number: response.number,
+      url: response.html_url,
+    })
+  }
+
+  async fn pull_request_state(&self, number: u64) -> Result<PullRequestState> {
+    let url = format!("{}/repos/{}/pulls/{}", self.api_url, self.repository_path, number);
+    let mut request = self.client.get(url);
+    if let Some(token) = &self.token {
+      request = request.header("Authorization", format!("token {}", token));
+    }
+
+    let response: GiteaPullRequestStateResponse = send(request).await?;
+    Ok(match (response.merged, response.state.as_str()) {
+      (true, _) => PullRequestState::Merged,
+      (false, "closed") => PullRequestState::Closed,
+      _ => PullRequestState::Open,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use mockito::{mock, Matcher};
+  use serde_json::json;
+
+  use crate::domain::model::{GitProvider, NewPullRequest, PullRequestState};
+  use crate::domain::ports::outgoing::PullRequestProvider;
+  use crate::infrastructure::pull_requests::github::tests::config;
+  use crate::infrastructure::pull_requests::GiteaPullRequestProvider;
+
+  #[tokio::test]
+  async fn create_a_pull_request() {
+    let mock = mock("POST", "/repos/gitea/gitops/pulls")
+      .match_header("authorization", "token secret")
+      .match_body(Matcher::Json(
+        json!({"title": "Add nodegroup", "body": "Body", "head": "add-nodegroup-1", "base": "dev"}),
+      ))
+      .with_status(201)
+      .with_body(r#"{"number": 3, "html_url": "https://gitea.example.com/gitea/gitops/pulls/3"}"#)
+      .create();
+    let provider = GiteaPullRequestProvider::new(&config("gitea/gitops", GitProvider::Gitea));
+    let new_pull_request = NewPullRequest {
+      title: "Add nodegroup".to_string(),
+      body: "Body".to_string(),
+      source_branch: "add-nodegroup-1".to_string(),
+    };
+
+    let pull_request = provider.create_pull_request(&new_pull_request).await.unwrap();
+
+    mock.assert();
+    assert_eq!(pull_request.number, 3);
+    assert_eq!(pull_request.url, "https://gitea.example.com/gitea/gitops/pulls/3");
+  }
+
+  #[tokio::test]
+  async fn read_the_pull_request_state() {
+    let open = mock("GET", "/repos/gitea/states/pulls/7")
+      .match_header("authorization", "token secret")
+      .with_body(r#"{"number": 7, "state": "open", "merged": false}"#)
+      .create();
+    let merged = mock("GET", "/repos/gitea/states/pulls/8")
+      .with_body(r#"{"number": 8, "state": "closed", "merged": true}"#)
+      .create();
+    let closed = mock("GET", "/repos/gitea/states/pulls/9")
+      .with_body(r#"{"number": 9, "state": "closed", "merged": false}"#)
+      .create();
+    let provider = GiteaPullRequestProvider::new(&config("gitea/states", GitProvider::Gitea));
+
+    assert_eq!(provider.pull_request_state(7).await.unwrap(), PullRequestState::Open);
+    assert_eq!(provider.pull_request_state(8).await.unwrap(), PullRequestState::Merged);
+    assert_eq!(provider.pull_request_state(9).await.unwrap(), PullRequestState::Closed);
+    open.assert();
+    merged.assert();
+    closed.assert();
+  }
+}
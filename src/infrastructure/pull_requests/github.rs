@@ -0,0 +1,177 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::domain::model::{GitOpsConfig, NewPullRequest, PullRequestDto, PullRequestState};
+use crate::domain::ports::outgoing::PullRequestProvider;
+use crate::infrastructure::pull_requests::{http_client, send};
+
+/// Opens Pull Requests through the GitHub (or GitHub Enterprise) REST API
+#[derive(Clone)]
+pub struct GitHubPullRequestProvider {
+  client: Client,
+  api_url: String,
+  repository_path: String,
+  base_branch: String,
+  token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GitHubPullRequestBody<'a> {
+  title: &'a str,
+  body: &'a str,
+  head: &'a str,
+  base: &'a str,
+}
+
+#[derive(Deserialize)]
+struct GitHubPullRequestResponse {
+  number: u64,
+  html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubPullRequestStateResponse {
+  state: String,
+  merged: bool,
+}
+
+impl GitHubPullRequestProvider {
+  pub fn new(config: &GitOpsConfig) -> Self {
+    Self {
+      client: http_client(),
+      api_url: config.api_url.trim_end_matches('/').to_string(),
+      repository_path: config.repository_path.clone(),
+      base_branch: config.branch.clone(),
+      token: config.token.clone(),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl PullRequestProvider for GitHubPullRequestProvider {
+  async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+    let url = format!("{}/repos/{}/pulls", self.api_url, self.repository_path);
+    let mut request = self
+      .client
+      .post(url)
+      .header("Accept", "application/vnd.github.v3+json")
+      .json(&GitHubPullRequestBody {
+        title: &pull_request.title,
+        body: &pull_request.body,
+        head: &pull_request.source_branch,
+        base: &self.base_branch,
+      });
+    if let Some(token) = &self.token {
+      request = request.header("Authorization", format!("token {}", token));
+    }
+
+    let response: GitHubPullRequestResponse = send(request).await?;
+    Ok(PullRequestDto {
+      number: response.number,
+      url: response.html_url,
+    })
+  }
+
+  async fn pull_request_state(&self, number: u64) -> Result<PullRequestState> {
+    let url = format!("{}/repos/{}/pulls/{}", self.api_url, self.repository_path, number);
+    let mut request = self.client.get(url).header("Accept", "application/vnd.github.v3+json");
+    if let Some(token) = &self.token {
+      request = request.header("Authorization", format!("token {}", token));
+    }
+
+    let response: GitHubPullRequestStateResponse = send(request).await?;
+    Ok(match (response.merged, response.state.as_str()) {
+      (true, _) => PullRequestState::Merged,
+      (false, "closed") => PullRequestState::Closed,
+      _ => PullRequestState::Open,
+    })
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use mockito::{mock, Matcher};
+  use serde_json::json;
+
+  use crate::domain::model::{GitOpsConfig, GitProvider, NewPullRequest, PullRequestState};
+  use crate::domain::ports::outgoing::PullRequestProvider;
+  use crate::infrastructure::pull_requests::GitHubPullRequestProvider;
+
+  pub fn config(repository_path: &str, provider: GitProvider) -> GitOpsConfig {
+    GitOpsConfig {
+      repository_name: "gitops".to_string(),
+      organization: "test".to_string(),
+      branch: "dev".to_string(),
+      destination_folder: "/tmp/gitops".to_string(),
+      mirror_folder: "/tmp/stores/gitops".to_string(),
+      repository_path: repository_path.to_string(),
+      repository_url: format!("{}/{}.git", mockito::server_url(), repository_path),
+      username: None,
+      token: Some("secret".to_string()),
+      author_name: "test".to_string(),
+      author_email: "test@example.com".to_string(),
+      provider,
+      api_url: mockito::server_url(),
+      max_concurrent_operations: 1,
+    }
+  }
+
+  fn new_pull_request() -> NewPullRequest {
+    NewPullRequest {
+      title: "Add nodegroup".to_string(),
+      body: "Body".to_string(),
+      source_branch: "add-nodegroup-1".to_string(),
+    }
+  }
+
+  #[tokio::test]
+  async fn create_a_pull_request() {
+    let mock = mock("POST", "/repos/github/gitops/pulls")
+      .match_header("authorization", "token secret")
+      .match_body(Matcher::Json(
+        json!({"title": "Add nodegroup", "body": "Body", "head": "add-nodegroup-1", "base": "dev"}),
+      ))
+      .with_status(201)
+      .with_body(r#"{"number": 42, "html_url": "https://github.com/github/gitops/pull/42"}"#)
+      .create();
+    let provider = GitHubPullRequestProvider::new(&config("github/gitops", GitProvider::GitHub));
+
+    let pull_request = provider.create_pull_request(&new_pull_request()).await.unwrap();
+
+    mock.assert();
+    assert_eq!(pull_request.number, 42);
+    assert_eq!(pull_request.url, "https://github.com/github/gitops/pull/42");
+  }
+
+  #[tokio::test]
+  async fn report_rejected_pull_requests() {
+    let _mock = mock("POST", "/repos/github/rejected/pulls")
+      .with_status(422)
+      .with_body(r#"{"message": "Validation Failed"}"#)
+      .create();
+    let provider = GitHubPullRequestProvider::new(&config("github/rejected", GitProvider::GitHub));
+
+    let error = provider.create_pull_request(&new_pull_request()).await.unwrap_err();
+
+    assert!(error.to_string().contains("422"));
+  }
+
+  #[tokio::test]
+  async fn read_the_pull_request_state() {
+    let merged = mock("GET", "/repos/github/states/pulls/42")
+      .match_header("authorization", "token secret")
+      .with_body(r#"{"number": 42, "state": "closed", "merged": true}"#)
+      .create();
+    let closed = mock("GET", "/repos/github/states/pulls/43")
+      .with_body(r#"{"number": 43, "state": "closed", "merged": false}"#)
+      .create();
+    let provider = GitHubPullRequestProvider::new(&config("github/states", GitProvider::GitHub));
+
+    assert_eq!(provider.pull_request_state(42).await.unwrap(), PullRequestState::Merged);
+    assert_eq!(provider.pull_request_state(43).await.unwrap(), PullRequestState::Closed);
+    merged.assert();
+    closed.assert();
+  }
+}
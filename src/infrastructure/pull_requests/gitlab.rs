@@ -0,0 +1,146 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::domain::model::{GitOpsConfig, NewPullRequest, PullRequestDto, PullRequestState};
+use crate::domain::ports::outgoing::PullRequestProvider;
+use crate::infrastructure::pull_requests::{http_client, send};
+
+/// Opens Merge Requests through the GitLab REST API
+#[derive(Clone)]
+pub struct GitLabPullRequestProvider {
+  client: Client,
+  api_url: String,
+  repository_path: String,
+  base_branch: String,
+  token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GitLabMergeRequestBody<'a> {
+  title: &'a str,
+  description: &'a str,
+  source_branch: &'a str,
+  target_branch: &'a str,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequestResponse {
+  iid: u64,
+  web_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabMergeRequestStateResponse {
+  state: String,
+}
+
+impl GitLabPullRequestProvider {
+  pub fn new(config: &GitOpsConfig) -> Self {
+    Self {
+      client: http_client(),
+      api_url: config.api_url.trim_end_matches('/').to_string(),
+      repository_path: config.repository_path.clone(),
+      base_branch: config.branch.clone(),
+      token: config.token.clone(),
+    }
+  }
+
+  /// GitLab identifies projects by their URL-encoded full path
+  fn project_id(&self) -> String {
+    self.repository_path.replace('/', "%2F")
+  }
+}
+
+#[async_trait(?Send)]
+impl PullRequestProvider for GitLabPullRequestProvider {
+  async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+    let url = format!("{}/projects/{}/merge_requests", self.api_url, self.project_id());
+    let mut request = self.client.post(url).json(&GitLabMergeRequestBody {
+      title: &pull_request.title,
+      description: &pull_request.body,
+      source_branch: &pull_request.source_branch,
+      target_branch: &self.base_branch,
+    });
+    if let Some(token) = &self.token {
+      request = request.header("PRIVATE-TOKEN", token.as_str());
+    }
+
+    let response: GitLabMergeRequestResponse = send(request).await?;
+    Ok(PullRequestDto {
+      number: response.iid,
+      url: response.web_url,
+    })
+  }
+
+  async fn pull_request_state(&self, number: u64) -> Result<PullRequestState> {
+    let url = format!("{}/projects/{}/merge_requests/{}", self.api_url, self.project_id(), number);
+    let mut request = self.client.get(url);
+    if let Some(token) = &self.token {
+      request = request.header("PRIVATE-TOKEN", token.as_str());
+    }
+
+    let response: GitLabMergeRequestStateResponse = send(request).await?;
+    Ok(match response.state.as_str() {
+      "merged" => PullRequestState::Merged,
+      "closed" => PullRequestState::Closed,
+      _ => PullRequestState::Open,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use mockito::{mock, Matcher};
+  use serde_json::json;
+
+  use crate::domain::model::{GitProvider, NewPullRequest, PullRequestState};
+  use crate::domain::ports::outgoing::PullRequestProvider;
+  use crate::infrastructure::pull_requests::github::tests::config;
+  use crate::infrastructure::pull_requests::GitLabPullRequestProvider;
+
+  #[tokio::test]
+  async fn create_a_merge_request() {
+    let mock = mock("POST", "/projects/gitlab%2Fgitops/merge_requests")
+      .match_header("private-token", "secret")
+      .match_body(Matcher::Json(json!({
+        "title": "Add nodegroup",
+        "description": "Body",
+        "source_branch": "add-nodegroup-1",
+        "target_branch": "dev"
+      })))
+      .with_status(201)
+      .with_body(r#"{"id": 1000, "iid": 7, "web_url": "https://gitlab.com/gitlab/gitops/-/merge_requests/7"}"#)
+      .create();
+    let provider = GitLabPullRequestProvider::new(&config("gitlab/gitops", GitProvider::GitLab));
+    let new_pull_request = NewPullRequest {
+      title: "Add nodegroup".to_string(),
+      body: "Body".to_string(),
+      source_branch: "add-nodegroup-1".to_string(),
+    };
+
+    let pull_request = provider.create_pull_request(&new_pull_request).await.unwrap();
+
+    mock.assert();
+    assert_eq!(pull_request.number, 7);
+    assert_eq!(pull_request.url, "https://gitlab.com/gitlab/gitops/-/merge_requests/7");
+  }
+
+  #[tokio::test]
+  async fn read_the_merge_request_state() {
+    let merged = mock("GET", "/projects/gitlab%2Fgitops/merge_requests/7")
+      .match_header("private-token", "secret")
+      .with_body(r#"{"iid": 7, "state": "merged"}"#)
+      .create();
+    let opened = mock("GET", "/projects/gitlab%2Fgitops/merge_requests/8")
+      .with_body(r#"{"iid": 8, "state": "opened"}"#)
+      .create();
+    let provider = GitLabPullRequestProvider::new(&config("gitlab/gitops", GitProvider::GitLab));
+
+    assert_eq!(provider.pull_request_state(7).await.unwrap(), PullRequestState::Merged);
+    assert_eq!(provider.pull_request_state(8).await.unwrap(), PullRequestState::Open);
+    merged.assert();
+    opened.assert();
+  }
+}
@@ -0,0 +1,72 @@
+pub mod errors;
+pub mod gitea;
+pub mod github;
+pub mod gitlab;
+
+pub use errors::PullRequestError;
+pub use gitea::GiteaPullRequestProvider;
+pub use github::GitHubPullRequestProvider;
+pub use gitlab::GitLabPullRequestProvider;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+
+use crate::domain::model::{GitOpsConfig, GitProvider, NewPullRequest, PullRequestDto, PullRequestState};
+use crate::domain::ports::outgoing::PullRequestProvider;
+
+const USER_AGENT: &str = "unicron-backend-api";
+
+/// PullRequestProvider selected at start-up from the configured git provider
+#[derive(Clone)]
+pub enum GitPullRequestProvider {
+  GitHub(GitHubPullRequestProvider),
+  GitLab(GitLabPullRequestProvider),
+  Gitea(GiteaPullRequestProvider),
+}
+
+impl GitPullRequestProvider {
+  pub fn new(config: &GitOpsConfig) -> Self {
+    match config.provider {
+      GitProvider::GitHub => GitPullRequestProvider::GitHub(GitHubPullRequestProvider::new(config)),
+      GitProvider::GitLab => GitPullRequestProvider::GitLab(GitLabPullRequestProvider::new(config)),
+      GitProvider::Gitea => GitPullRequestProvider::Gitea(GiteaPullRequestProvider::new(config)),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl PullRequestProvider for GitPullRequestProvider {
+  async fn create_pull_request(&self, pull_request: &NewPullRequest) -> Result<PullRequestDto> {
+    match self {
+      GitPullRequestProvider::GitHub(provider) => provider.create_pull_request(pull_request).await,
+      GitPullRequestProvider::GitLab(provider) => provider.create_pull_request(pull_request).await,
+      GitPullRequestProvider::Gitea(provider) => provider.create_pull_request(pull_request).await,
+    }
+  }
+
+  async fn pull_request_state(&self, number: u64) -> Result<PullRequestState> {
+    match self {
+      GitPullRequestProvider::GitHub(provider) => provider.pull_request_state(number).await,
+      GitPullRequestProvider::GitLab(provider) => provider.pull_request_state(number).await,
+      GitPullRequestProvider::Gitea(provider) => provider.pull_request_state(number).await,
+    }
+  }
+}
+
+fn http_client() -> Client {
+  Client::builder().user_agent(USER_AGENT).build().unwrap_or_else(|_| Client::new())
+}
+
+/// Sends the request and deserializes the body of a successful response
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, PullRequestError> {
+  let response = request.send().await.map_err(PullRequestError::Request)?;
+  let status = response.status();
+  if status.is_success() {
+    response.json::<T>().await.map_err(PullRequestError::Request)
+  } else {
+    let body = response.text().await.unwrap_or_default();
+    Err(PullRequestError::Rejected(status.as_u16(), body))
+  }
+}
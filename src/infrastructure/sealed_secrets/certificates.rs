@@ -0,0 +1,180 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use log::warn;
+use openssl::asn1::Asn1Time;
+use openssl::x509::X509;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::domain::model::SealingCertificateDto;
+use crate::domain::ports::outgoing::CertificateProvider;
+
+/// Loads the PEM certificate of the sealed-secrets controller from its source
+#[async_trait(?Send)]
+pub trait CertificateLoader {
+  async fn load(&self) -> Result<String>;
+}
+
+/// Certificate loaded at a given time
+#[derive(Clone)]
+struct CachedCertificate {
+  certificate: SealingCertificateDto,
+  loaded_at: Instant,
+}
+
+/// Keeps the certificate of the controller for the TTL before loading it again. The cached certificate is still used
+/// when it can't be reloaded, until it expires.
+#[derive(Clone)]
+pub struct CachedCertificateProvider<L: CertificateLoader> {
+  loader: L,
+  ttl: Duration,
+  cache: Arc<Mutex<Option<CachedCertificate>>>,
+}
+
+impl<L: CertificateLoader> CachedCertificateProvider<L> {
+  pub fn new(loader: L, ttl: Duration) -> Self {
+    Self {
+      loader,
+      ttl,
+      cache: Arc::new(Mutex::new(None)),
+    }
+  }
+
+  async fn reload(&self) -> Result<SealingCertificateDto> {
+    let certificate = parse_certificate(&self.loader.load().await?)?;
+    check_expiry(&certificate)?;
+    *self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(CachedCertificate {
+      certificate: certificate.clone(),
+      loaded_at: Instant::now(),
+    });
+    Ok(certificate)
+  }
+}
+
+#[async_trait(?Send)]
+impl<L: CertificateLoader> CertificateProvider for CachedCertificateProvider<L> {
+  async fn certificate(&self) -> Result<SealingCertificateDto> {
+    let cached = self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    let cached = cached.filter(|cached| check_expiry(&cached.certificate).is_ok());
+    match cached {
+      Some(cached) if cached.loaded_at.elapsed() < self.ttl => Ok(cached.certificate),
+      cached => match (self.reload().await, cached) {
+        (Err(err), Some(cached)) => {
+          warn!("Error reloading the sealed-secrets certificate, keeping the cached one: {:?}", err);
+          Ok(cached.certificate)
+        }
+        (reloaded, _) => reloaded,
+      },
+    }
+  }
+}
+
+/// Reads the PEM certificate and its expiry. The controller only uses RSA keys.
+pub fn parse_certificate(pem: &str) -> Result<SealingCertificateDto> {
+  let certificate = X509::from_pem(pem.as_bytes()).context("Invalid sealed-secrets certificate")?;
+  if certificate.public_key().and_then(|key| key.rsa()).is_err() {
+    return Err(anyhow!("The sealed-secrets certificate doesn't hold an RSA key"));
+  }
+  let expiry = Asn1Time::from_unix(0)?.diff(certificate.not_after())?;
+  Ok(SealingCertificateDto {
+    pem: pem.to_string(),
+    not_after: i64::from(expiry.days) * 86400 + i64::from(expiry.secs),
+  })
+}
+
+fn check_expiry(certificate: &SealingCertificateDto) -> Result<()> {
+  let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+  if certificate.not_after > now {
+    return Ok(());
+  }
+  let expiry = Asn1Time::from_unix(certificate.not_after as _)?;
+  Err(anyhow!("The sealed-secrets certificate expired on {}", &*expiry))
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::{anyhow, Result};
+  use async_trait::async_trait;
+  use openssl::asn1::Asn1Time;
+  use std::cell::RefCell;
+  use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+  use crate::domain::ports::outgoing::CertificateProvider;
+  use crate::infrastructure::sealed_secrets::certificates::{parse_certificate, CachedCertificateProvider, CertificateLoader};
+  use crate::infrastructure::sealed_secrets::client::tests::{controller_keys, signed_certificate};
+
+  /// Returns the certificates in turn, counting the loads
+  struct SequenceLoader {
+    certificates: RefCell<Vec<Result<String>>>,
+    loads: RefCell<usize>,
+  }
+
+  impl SequenceLoader {
+    fn new(certificates: Vec<Result<String>>) -> Self {
+      Self {
+        certificates: RefCell::new(certificates),
+        loads: RefCell::new(0),
+      }
+    }
+  }
+
+  #[async_trait(?Send)]
+  impl CertificateLoader for SequenceLoader {
+    async fn load(&self) -> Result<String> {
+      *self.loads.borrow_mut() += 1;
+      self.certificates.borrow_mut().remove(0)
+    }
+  }
+
+  fn pem() -> String {
+    String::from_utf8(controller_keys().1).unwrap()
+  }
+
+  #[test]
+  fn read_the_expiry_of_the_certificate() {
+    let (key, _) = controller_keys();
+    let certificate = signed_certificate(&key, &Asn1Time::from_unix(1_893_456_000).unwrap());
+
+    let certificate = parse_certificate(std::str::from_utf8(&certificate).unwrap()).unwrap();
+
+    assert_eq!(certificate.not_after, 1_893_456_000);
+    assert!(parse_certificate("not a certificate").is_err());
+  }
+
+  #[tokio::test]
+  async fn cache_the_certificate_for_the_ttl() {
+    let (first, second) = (pem(), pem());
+    let provider = CachedCertificateProvider::new(
+      SequenceLoader::new(vec![Ok(first.clone()), Ok(second.clone())]),
+      Duration::from_secs(3600),
+    );
+
+    assert_eq!(provider.certificate().await.unwrap().pem, first);
+    assert_eq!(provider.certificate().await.unwrap().pem, first);
+    assert_eq!(*provider.loader.loads.borrow(), 1);
+
+    let provider = CachedCertificateProvider::new(
+      SequenceLoader::new(vec![Ok(first.clone()), Ok(second.clone()), Err(anyhow!("Controller down"))]),
+      Duration::from_secs(0),
+    );
+    assert_eq!(provider.certificate().await.unwrap().pem, first);
+    assert_eq!(provider.certificate().await.unwrap().pem, second);
+    assert_eq!(provider.certificate().await.unwrap().pem, second);
+    assert_eq!(*provider.loader.loads.borrow(), 3);
+  }
+
+  #[tokio::test]
+  async fn reject_the_expired_certificates() {
+    let (key, _) = controller_keys();
+    let yesterday = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 - 86400;
+    let expired = signed_certificate(&key, &Asn1Time::from_unix(yesterday).unwrap());
+    let provider = CachedCertificateProvider::new(
+      SequenceLoader::new(vec![Ok(String::from_utf8(expired).unwrap())]),
+      Duration::from_secs(3600),
+    );
+
+    let error = provider.certificate().await.unwrap_err();
+
+    assert!(error.to_string().contains("expired"), "{}", error);
+  }
+}
@@ -0,0 +1,352 @@
+use anyhow::{anyhow, Context, Result};
+use openssl::encrypt::Encrypter;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Public};
+use openssl::rand::rand_bytes;
+use openssl::rsa::Padding;
+use openssl::symm::{encrypt_aead, Cipher};
+use openssl::x509::X509;
+use std::collections::BTreeMap;
+use std::fs::write;
+
+use crate::domain::model::{SealedSecretDto, SealingCertificateDto, SealingScope, SealingTarget, SecretData};
+use crate::domain::ports::outgoing::SealedSecretClient;
+use crate::infrastructure::sealed_secrets::manifest::{merge_encrypted_data, SealedSecretManifest};
+use crate::utils::validators::secrets::is_secret_key;
+
+/// Length of the AES-256-GCM session keys
+const SESSION_KEY_LENGTH: usize = 32;
+/// Length of the AES-GCM nonces. The session keys are used once, so the nonce is always zero
+const NONCE_LENGTH: usize = 12;
+/// Length of the AES-GCM authentication tags
+const TAG_LENGTH: usize = 16;
+
+/// Seals the secrets in process with the public certificate of the sealed-secrets controller, like `kubeseal` does
+#[derive(Clone, Default)]
+pub struct NativeSealedSecretClient;
+
+impl NativeSealedSecretClient {
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Label binding the ciphertexts to the secrets the scope allows to unseal them into
+  fn label(target: &SealingTarget, name: &str) -> String {
+    match target.scope {
+      SealingScope::Strict => format!("{}/{}", target.namespace, name),
+      SealingScope::NamespaceWide => target.namespace.clone(),
+      SealingScope::ClusterWide => String::new(),
+    }
+  }
+
+  /// Annotations telling the controller the scope of the sealed secret
+  fn annotations(target: &SealingTarget) -> BTreeMap<String, String> {
+    let annotation = match target.scope {
+      SealingScope::Strict => return BTreeMap::new(),
+      SealingScope::NamespaceWide => "sealedsecrets.bitnami.com/namespace-wide",
+      SealingScope::ClusterWide => "sealedsecrets.bitnami.com/cluster-wide",
+    };
+    vec![(annotation.to_string(), "true".to_string())].into_iter().collect()
+  }
+
+  /// Scope of a sealed secret from its annotations, strict without any
+  fn scope(annotations: &BTreeMap<String, String>) -> SealingScope {
+    let enabled = |annotation: &str| annotations.get(annotation).map(String::as_str) == Some("true");
+    match (
+      enabled("sealedsecrets.bitnami.com/cluster-wide"),
+      enabled("sealedsecrets.bitnami.com/namespace-wide"),
+    ) {
+      (true, _) => SealingScope::ClusterWide,
+      (false, true) => SealingScope::NamespaceWide,
+      (false, false) => SealingScope::Strict,
+    }
+  }
+
+  /// Encrypts the value with the hybrid scheme of sealed-secrets: a random AES-256-GCM session key, encrypted with
+  /// RSA-OAEP (SHA-256) and the label, prefixed by its 2-byte big-endian length and followed by the AES-GCM ciphertext
+  /// and tag of the value.
+  fn encrypt(public_key: &PKey<Public>, label: &[u8], value: &[u8]) -> Result<Vec<u8>> {
+    let mut session_key = [0; SESSION_KEY_LENGTH];
+    rand_bytes(&mut session_key)?;
+
+    let mut encrypter = Encrypter::new(public_key)?;
+    encrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+    encrypter.set_rsa_oaep_md(MessageDigest::sha256())?;
+    encrypter.set_rsa_mgf1_md(MessageDigest::sha256())?;
+    if !label.is_empty() {
+      encrypter.set_rsa_oaep_label(label)?;
+    }
+    let mut encrypted_key = vec![0; encrypter.encrypt_len(&session_key)?];
+    let length = encrypter.encrypt(&session_key, &mut encrypted_key)?;
+    encrypted_key.truncate(length);
+
+    let mut tag = [0; TAG_LENGTH];
+    let ciphertext = encrypt_aead(Cipher::aes_256_gcm(), &session_key, Some(&[0; NONCE_LENGTH]), &[], value, &mut tag)?;
+
+    let mut sealed = Vec::with_capacity(2 + encrypted_key.len() + ciphertext.len() + TAG_LENGTH);
+    sealed.extend_from_slice(&(encrypted_key.len() as u16).to_be_bytes());
+    sealed.extend_from_slice(&encrypted_key);
+    sealed.extend_from_slice(&ciphertext);
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+  }
+
+  fn seal(&self, secret: &SecretData, target: &SealingTarget, certificate: &SealingCertificateDto) -> Result<SealedSecretManifest> {
+    let public_key = X509::from_pem(certificate.pem.as_bytes())
+      .and_then(|certificate| certificate.public_key())
+      .context("Invalid sealed-secrets certificate")?;
+    let label = Self::label(target, &secret.name);
+    let encrypted_data = secret
+      .data
+      .iter()
+      .map(|(key, value)| {
+        if !is_secret_key(key) {
+          return Err(anyhow!("Invalid secret key {}: only alphanumeric characters, '-', '_' or '.'", key));
+        }
+        let sealed = Self::encrypt(&public_key, label.as_bytes(), value)?;
+        Ok((key.clone(), base64::encode(sealed)))
+      })
+      .collect::<Result<BTreeMap<_, _>>>()?;
+
+    Ok(SealedSecretManifest {
+      name: secret.name.clone(),
+      namespace: target.namespace.clone(),
+      annotations: Self::annotations(target),
+      secret_type: secret.secret_type.clone(),
+      encrypted_data,
+    })
+  }
+}
+
+impl SealedSecretClient for NativeSealedSecretClient {
+  fn save(
+    &self,
+    secret: &SecretData,
+    target: &SealingTarget,
+    certificate: &SealingCertificateDto,
+    destination: Option<String>,
+  ) -> Result<()> {
+    let destination = destination.ok_or_else(|| anyhow!("Missing destination of the sealed secret {}", secret.name))?;
+    write(destination, self.render(secret, target, certificate)?).map_err(|err| err.into())
+  }
+
+  fn render(&self, secret: &SecretData, target: &SealingTarget, certificate: &SealingCertificateDto) -> Result<String> {
+    self.seal(secret, target, certificate).map(|manifest| manifest.to_yaml())
+  }
+
+  fn read(&self, manifest: &str) -> Result<SealedSecretDto> {
+    let manifest = SealedSecretManifest::from_yaml(manifest)?;
+    Ok(SealedSecretDto {
+      scope: Self::scope(&manifest.annotations),
+      keys: manifest.encrypted_data.keys().cloned().collect(),
+      name: manifest.name,
+      namespace: manifest.namespace,
+      secret_type: manifest.secret_type,
+    })
+  }
+
+  fn merge(
+    &self,
+    manifest: &str,
+    secret: &SecretData,
+    removed: &[String],
+    target: &SealingTarget,
+    certificate: &SealingCertificateDto,
+  ) -> Result<String> {
+    let sealed = self.seal(secret, target, certificate)?;
+    merge_encrypted_data(manifest, &sealed.encrypted_data, removed)
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use openssl::asn1::{Asn1Time, Asn1TimeRef};
+  use openssl::encrypt::Decrypter;
+  use openssl::hash::MessageDigest;
+  use openssl::pkey::{PKey, Private};
+  use openssl::rsa::{Padding, Rsa};
+  use openssl::symm::{decrypt_aead, Cipher};
+  use openssl::x509::{X509NameBuilder, X509};
+
+  use crate::domain::model::{SealingCertificateDto, SealingScope, SealingTarget, SecretData};
+  use crate::domain::ports::outgoing::SealedSecretClient;
+  use crate::infrastructure::sealed_secrets::certificates::parse_certificate;
+  use crate::infrastructure::sealed_secrets::client::NativeSealedSecretClient;
+
+  /// Key pair of a test controller, with its self-signed PEM certificate
+  pub fn controller_keys() -> (PKey<Private>, Vec<u8>) {
+    let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    let certificate = signed_certificate(&key, &Asn1Time::days_from_now(3650).unwrap());
+    (key, certificate)
+  }
+
+  /// Self-signed PEM certificate of the key, valid until `not_after`
+  pub fn signed_certificate(key: &PKey<Private>, not_after: &Asn1TimeRef) -> Vec<u8> {
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", "sealed-secret").unwrap();
+    let name = name.build();
+    let mut certificate = X509::builder().unwrap();
+    certificate.set_version(2).unwrap();
+    certificate.set_subject_name(&name).unwrap();
+    certificate.set_issuer_name(&name).unwrap();
+    certificate.set_pubkey(key).unwrap();
+    certificate.set_not_before(&Asn1Time::from_unix(0).unwrap()).unwrap();
+    certificate.set_not_after(not_after).unwrap();
+    certificate.sign(key, MessageDigest::sha256()).unwrap();
+    certificate.build().to_pem().unwrap()
+  }
+
+  /// Decrypts a value like the sealed-secrets controller does
+  pub fn unseal(key: &PKey<Private>, label: &str, ciphertext: &str) -> Option<String> {
+    let sealed = base64::decode(ciphertext).ok()?;
+    let key_length = u16::from_be_bytes([sealed[0], sealed[1]]) as usize;
+    let (encrypted_key, rest) = sealed[2..].split_at(key_length);
+    let (ciphertext, tag) = rest.split_at(rest.len() - 16);
+
+    let mut decrypter = Decrypter::new(key).ok()?;
+    decrypter.set_rsa_padding(Padding::PKCS1_OAEP).ok()?;
+    decrypter.set_rsa_oaep_md(MessageDigest::sha256()).ok()?;
+    decrypter.set_rsa_mgf1_md(MessageDigest::sha256()).ok()?;
+    if !label.is_empty() {
+      decrypter.set_rsa_oaep_label(label.as_bytes()).ok()?;
+    }
+    let mut session_key = vec![0; decrypter.decrypt_len(encrypted_key).ok()?];
+    let length = decrypter.decrypt(encrypted_key, &mut session_key).ok()?;
+    session_key.truncate(length);
+
+    let value = decrypt_aead(Cipher::aes_256_gcm(), &session_key, Some(&[0; 12]), &[], ciphertext, tag).ok()?;
+    String::from_utf8(value).ok()
+  }
+
+  /// Ciphertext of the key in the rendered sealed secret
+  pub fn ciphertext(manifest: &str, key: &str) -> String {
+    let prefix = format!("    {}: ", key);
+    manifest
+      .lines()
+      .find_map(|line| line.strip_prefix(&prefix))
+      .map(String::from)
+      .unwrap_or_else(|| panic!("No ciphertext for {} in {}", key, manifest))
+  }
+
+  fn request() -> SecretData {
+    SecretData {
+      name: "db-credentials".to_string(),
+      secret_type: "Opaque".to_string(),
+      data: vec![
+        ("username".to_string(), b"admin".to_vec()),
+        ("password".to_string(), b"p@ss word=$(rm -rf /);".to_vec()),
+      ]
+      .into_iter()
+      .collect(),
+    }
+  }
+
+  fn target(scope: SealingScope) -> SealingTarget {
+    SealingTarget {
+      namespace: "payments".to_string(),
+      scope,
+    }
+  }
+
+  fn sealing_certificate(pem: &[u8]) -> SealingCertificateDto {
+    parse_certificate(std::str::from_utf8(pem).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn seal_the_values_for_the_controller() {
+    let (key, certificate) = controller_keys();
+    let certificate = sealing_certificate(&certificate);
+    let client = NativeSealedSecretClient::new();
+    let target = target(SealingScope::Strict);
+
+    let manifest = client.render(&request(), &target, &certificate).unwrap();
+
+    assert!(manifest.contains("kind: SealedSecret\nmetadata:\n  name: db-credentials\n  namespace: payments\n"));
+    assert!(manifest.contains("    type: Opaque\n"));
+    let password = ciphertext(&manifest, "password");
+    assert_eq!(
+      unseal(&key, "payments/db-credentials", &password).as_deref(),
+      Some("p@ss word=$(rm -rf /);")
+    );
+    assert_eq!(
+      unseal(&key, "payments/db-credentials", &ciphertext(&manifest, "username")).as_deref(),
+      Some("admin")
+    );
+    assert_eq!(unseal(&key, "payments/other-secret", &password), None);
+    assert_ne!(
+      password,
+      ciphertext(&client.render(&request(), &target, &certificate).unwrap(), "password")
+    );
+  }
+
+  #[test]
+  fn bind_the_values_to_the_scope() {
+    let (key, certificate) = controller_keys();
+    let certificate = sealing_certificate(&certificate);
+    let client = NativeSealedSecretClient::new();
+
+    let manifest = client
+      .render(&request(), &target(SealingScope::NamespaceWide), &certificate)
+      .unwrap();
+    assert!(manifest.contains("    sealedsecrets.bitnami.com/namespace-wide: \"true\"\n"));
+    assert_eq!(
+      unseal(&key, "payments", &ciphertext(&manifest, "username")).as_deref(),
+      Some("admin")
+    );
+
+    let manifest = client.render(&request(), &target(SealingScope::ClusterWide), &certificate).unwrap();
+    assert!(manifest.contains("    sealedsecrets.bitnami.com/cluster-wide: \"true\"\n"));
+    assert_eq!(unseal(&key, "", &ciphertext(&manifest, "username")).as_deref(), Some("admin"));
+  }
+
+  #[test]
+  fn reject_the_invalid_keys_and_certificates() {
+    let (_, certificate) = controller_keys();
+    let certificate = sealing_certificate(&certificate);
+    let client = NativeSealedSecretClient::new();
+    let target = target(SealingScope::Strict);
+    let invalid_key = SecretData {
+      data: vec![("api key".to_string(), b"value".to_vec())].into_iter().collect(),
+      ..request()
+    };
+
+    assert!(client.render(&invalid_key, &target, &certificate).is_err());
+    let invalid = SealingCertificateDto {
+      pem: "not a certificate".to_string(),
+      ..certificate
+    };
+    assert!(client.render(&request(), &target, &invalid).is_err());
+  }
+
+  #[test]
+  fn merge_the_keys_into_the_sealed_secret() {
+    let (key, certificate) = controller_keys();
+    let certificate = sealing_certificate(&certificate);
+    let client = NativeSealedSecretClient::new();
+    let target = target(SealingScope::NamespaceWide);
+    let manifest = client.render(&request(), &target, &certificate).unwrap();
+
+    let sealed = client.read(&manifest).unwrap();
+    assert_eq!(sealed.name, "db-credentials");
+    assert_eq!(sealed.namespace, "payments");
+    assert_eq!(sealed.scope, SealingScope::NamespaceWide);
+    assert_eq!(sealed.secret_type, "Opaque");
+    assert_eq!(sealed.keys, vec!["password".to_string(), "username".to_string()]);
+
+    let changes = SecretData {
+      data: vec![("api-key".to_string(), b"42".to_vec())].into_iter().collect(),
+      ..request()
+    };
+    let merged = client
+      .merge(&manifest, &changes, &["password".to_string()], &target, &certificate)
+      .unwrap();
+
+    assert_eq!(ciphertext(&merged, "username"), ciphertext(&manifest, "username"));
+    assert_eq!(unseal(&key, "payments", &ciphertext(&merged, "api-key")).as_deref(), Some("42"));
+    assert!(!merged.contains("    password: "));
+    assert_eq!(
+      client.read(&merged).unwrap().keys,
+      vec!["api-key".to_string(), "username".to_string()]
+    );
+  }
+}
@@ -0,0 +1,401 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+
+lazy_static! {
+  static ref PLAIN_SCALAR_REGEX: Regex = Regex::new(r"^[A-Za-z_][-._A-Za-z0-9]*$").unwrap();
+}
+
+/// Path of the block holding the ciphertexts
+const ENCRYPTED_DATA_PATH: [&str; 2] = ["spec", "encryptedData"];
+
+/// Plain scalars read as booleans or null by the YAML parsers
+const RESERVED_SCALARS: [&str; 9] = ["true", "false", "null", "yes", "no", "on", "off", "y", "n"];
+
+/// `SealedSecret` resource, as written to the GitOps repository
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SealedSecretManifest {
+  pub name: String,
+  pub namespace: String,
+  /// `sealedsecrets.bitnami.com/*` annotations of the sealed secret and its template
+  pub annotations: BTreeMap<String, String>,
+  /// Type of the unsealed secret
+  pub secret_type: String,
+  /// Base64 ciphertext of every key of the secret
+  pub encrypted_data: BTreeMap<String, String>,
+}
+
+impl SealedSecretManifest {
+  /// Reads a manifest rendered by `kubeseal -o yaml` or by this client. Only the block mappings and single-line scalars
+  /// of the fields used here are read, flow collections and multi-line scalars aren't supported.
+  pub fn from_yaml(content: &str) -> Result<Self> {
+    let entries = entries(content);
+    let value = |path: &[&str]| entries.iter().find(|entry| entry.path == path).map(|entry| unquote(entry.value));
+    let children = |path: &[&str]| {
+      entries
+        .iter()
+        .filter(|entry| entry.path.len() == path.len() + 1 && entry.path[..path.len()] == *path)
+        .map(|entry| (entry.path[path.len()].to_string(), unquote(entry.value)))
+        .collect::<BTreeMap<_, _>>()
+    };
+
+    if value(&["kind"]).as_deref() != Some("SealedSecret") {
+      return Err(anyhow!("Not a SealedSecret manifest"));
+    }
+    let name = value(&["metadata", "name"]).ok_or_else(|| anyhow!("The sealed secret has no name"))?;
+    let namespace = value(&["metadata", "namespace"]).ok_or_else(|| anyhow!("The sealed secret {} has no namespace", name))?;
+    Ok(Self {
+      annotations: children(&["metadata", "annotations"])
+        .into_iter()
+        .filter(|(key, _)| key.starts_with("sealedsecrets.bitnami.com/"))
+        .collect(),
+      secret_type: value(&["spec", "template", "type"]).unwrap_or_else(|| "Opaque".to_string()),
+      encrypted_data: children(&ENCRYPTED_DATA_PATH),
+      name,
+      namespace,
+    })
+  }
+
+  /// Renders the manifest like `kubeseal -o yaml`. The strings are quoted when they wouldn't be read back as such.
+  pub fn to_yaml(&self) -> String {
+    let mut lines = vec![
+      "apiVersion: bitnami.com/v1alpha1".to_string(),
+      "kind: SealedSecret".to_string(),
+      "metadata:".to_string(),
+    ];
+    lines.extend(self.metadata("  "));
+    lines.push("spec:".to_string());
+    lines.push("  encryptedData:".to_string());
+    lines.extend(
+      self
+        .encrypted_data
+        .iter()
+        .map(|(key, ciphertext)| format!("    {}: {}", scalar(key), ciphertext)),
+    );
+    lines.push("  template:".to_string());
+    lines.push("    metadata:".to_string());
+    lines.extend(self.metadata("      "));
+    lines.push(format!("    type: {}", scalar(&self.secret_type)));
+
+    let mut yaml = lines.join("\n");
+    yaml.push('\n');
+    yaml
+  }
+
+  fn metadata(&self, indent: &str) -> Vec<String> {
+    let mut lines = vec![];
+    if !self.annotations.is_empty() {
+      lines.push(format!("{}annotations:", indent));
+      lines.extend(
+        self
+          .annotations
+          .iter()
+          .map(|(key, value)| format!("{}  {}: {}", indent, key, quoted(value))),
+      );
+    }
+    lines.push(format!("{}name: {}", indent, scalar(&self.name)));
+    lines.push(format!("{}namespace: {}", indent, scalar(&self.namespace)));
+    lines
+  }
+}
+
+/// Replaces the ciphertexts of the keys and removes the `removed` keys in the manifest. Only the `encryptedData` block
+/// is rewritten, sorted by key: the other ciphertexts and the rest of the manifest are kept as they are.
+pub fn merge_encrypted_data(content: &str, ciphertexts: &BTreeMap<String, String>, removed: &[String]) -> Result<String> {
+  let lines: Vec<&str> = content.lines().collect();
+  let entries = entries(content);
+  let header = entries
+    .iter()
+    .find(|entry| entry.path == ENCRYPTED_DATA_PATH)
+    .ok_or_else(|| anyhow!("The sealed secret has no spec.encryptedData"))?;
+  if !header.value.is_empty() && header.value != "{}" {
+    return Err(anyhow!("Unsupported spec.encryptedData value {}", header.value));
+  }
+
+  // The block runs until the next line that isn't indented deeper than its header
+  let end = (header.index + 1..lines.len())
+    .find(|&index| !is_ignored(lines[index]) && indentation(lines[index]) <= header.indent)
+    .unwrap_or(lines.len());
+  let items: Vec<&Entry> = entries
+    .iter()
+    .filter(|entry| entry.index > header.index && entry.index < end)
+    .collect();
+  let unsupported = (header.index + 1..end).any(|index| {
+    !is_ignored(lines[index])
+      && !items
+        .iter()
+        .any(|item| item.index == index && item.path.len() == 3 && !item.value.is_empty())
+  });
+  if unsupported {
+    return Err(anyhow!(
+      "Unsupported layout of spec.encryptedData: only single-line ciphertexts can be merged"
+    ));
+  }
+
+  let indent = items.first().map_or(header.indent + 2, |item| item.indent);
+  let mut block: BTreeMap<String, String> = items
+    .iter()
+    .map(|item| (item.path[2].clone(), lines[item.index].to_string()))
+    .collect();
+  for key in removed {
+    block.remove(key);
+  }
+  for (key, ciphertext) in ciphertexts {
+    block.insert(key.clone(), format!("{}{}: {}", " ".repeat(indent), scalar(key), ciphertext));
+  }
+
+  let header_line = match block.is_empty() {
+    true => format!("{}encryptedData: {{}}", " ".repeat(header.indent)),
+    false => format!("{}encryptedData:", " ".repeat(header.indent)),
+  };
+  let mut merged: Vec<String> = lines[..header.index].iter().map(|line| line.to_string()).collect();
+  merged.push(header_line);
+  merged.extend(block.into_values());
+  merged.extend(lines[end..].iter().map(|line| line.to_string()));
+
+  let mut yaml = merged.join("\n");
+  if content.ends_with('\n') {
+    yaml.push('\n');
+  }
+  Ok(yaml)
+}
+
+/// Key of a block mapping, with the path of its parents and its raw value
+struct Entry<'a> {
+  index: usize,
+  indent: usize,
+  path: Vec<String>,
+  value: &'a str,
+}
+
+/// Lists the keys of the block mappings of the document with their line. Sequences, flow collections and the
+/// continuation lines of multi-line scalars are skipped.
+fn entries(content: &str) -> Vec<Entry<'_>> {
+  let mut parents: Vec<(usize, String)> = vec![];
+  let mut entries = vec![];
+  for (index, line) in content.lines().enumerate() {
+    let trimmed = line.trim_start();
+    if is_ignored(line) || trimmed.starts_with('-') || trimmed.starts_with('{') {
+      continue;
+    }
+    let (key, value) = match split_entry(trimmed) {
+      Some(entry) => entry,
+      None => continue,
+    };
+    let indent = indentation(line);
+    while let Some((parent_indent, _)) = parents.last() {
+      if *parent_indent < indent {
+        break;
+      }
+      parents.pop();
+    }
+    parents.push((indent, key));
+    entries.push(Entry {
+      index,
+      indent,
+      path: parents.iter().map(|(_, key)| key.clone()).collect(),
+      value,
+    });
+  }
+  entries
+}
+
+/// Blank lines, comments and document markers
+fn is_ignored(line: &str) -> bool {
+  let trimmed = line.trim();
+  trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" || trimmed == "..."
+}
+
+fn indentation(line: &str) -> usize {
+  line.len() - line.trim_start().len()
+}
+
+/// Splits `key: value` into the unquoted key and the raw value
+fn split_entry(line: &str) -> Option<(String, &str)> {
+  let (key, rest) = match line.chars().next()? {
+    quote @ ('"' | '\'') => {
+      let end = closing_quote(line, quote)?;
+      (unquote(&line[..=end]), &line[end + 1..])
+    }
+    _ => {
+      let end = line.find(": ").or_else(|| line.strip_suffix(':').map(str::len))?;
+      (line[..end].trim_end().to_string(), &line[end..])
+    }
+  };
+  let value = rest.strip_prefix(':')?;
+  if !value.is_empty() && !value.starts_with(' ') {
+    return None;
+  }
+  Some((key, value.trim()))
+}
+
+/// Position of the quote closing the scalar opened at the start of the line
+fn closing_quote(line: &str, quote: char) -> Option<usize> {
+  let mut chars = line.char_indices().skip(1).peekable();
+  while let Some((index, char)) = chars.next() {
+    match char {
+      '\\' if quote == '"' => {
+        chars.next();
+      }
+      '\'' if quote == '\'' && chars.peek().map(|(_, next)| *next) == Some('\'') => {
+        chars.next();
+      }
+      char if char == quote => return Some(index),
+      _ => {}
+    }
+  }
+  None
+}
+
+/// Reads a single-line scalar, dropping the trailing comment of the plain ones
+fn unquote(value: &str) -> String {
+  if value.starts_with('"') {
+    if let Some(end) = closing_quote(value, '"') {
+      return serde_json::from_str(&value[..=end]).unwrap_or_else(|_| value[1..end].to_string());
+    }
+  }
+  if value.starts_with('\'') {
+    if let Some(end) = closing_quote(value, '\'') {
+      return value[1..end].replace("''", "'");
+    }
+  }
+  value.split(" #").next().unwrap_or_default().trim().to_string()
+}
+
+/// Keeps the value plain when it can't be mistaken for another type, quotes it otherwise
+fn scalar(value: &str) -> String {
+  match PLAIN_SCALAR_REGEX.is_match(value) && !RESERVED_SCALARS.contains(&value.to_lowercase().as_str()) {
+    true => value.to_string(),
+    false => quoted(value),
+  }
+}
+
+/// JSON strings are valid YAML double-quoted scalars
+fn quoted(value: &str) -> String {
+  serde_json::Value::String(value.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use crate::infrastructure::sealed_secrets::manifest::{merge_encrypted_data, SealedSecretManifest};
+
+  /// Sealed secret as written by `kubeseal -o yaml`
+  const KUBESEAL_MANIFEST: &str = r#"---
+apiVersion: bitnami.com/v1alpha1
+kind: SealedSecret
+metadata:
+  annotations:
+    sealedsecrets.bitnami.com/cluster-wide: 'true'
+  creationTimestamp: null
+  name: "db-credentials"
+  namespace: payments # managed by the platform team
+spec:
+  encryptedData:
+    "2fa.code": AgCbLy0Bkj6Mhxl3Bw==
+    password: AgBy3i4OJSWK+PiTySYZZA==
+  template:
+    data: null
+    metadata:
+      creationTimestamp: null
+      name: db-credentials
+      namespace: payments
+    type: kubernetes.io/dockerconfigjson
+"#;
+
+  #[test]
+  fn render_the_sealed_secret() {
+    let manifest = SealedSecretManifest {
+      name: "db-credentials".to_string(),
+      namespace: "payments".to_string(),
+      annotations: vec![("sealedsecrets.bitnami.com/namespace-wide".to_string(), "true".to_string())]
+        .into_iter()
+        .collect(),
+      secret_type: "Opaque".to_string(),
+      encrypted_data: vec![
+        ("password".to_string(), "AgBy3i4OJSWK+PiTySYZZA==".to_string()),
+        ("true".to_string(), "AgAKAoiQm7QDVeXf3w==".to_string()),
+        ("2fa.code".to_string(), "AgCbLy0Bkj6Mhxl3Bw==".to_string()),
+      ]
+      .into_iter()
+      .collect::<BTreeMap<_, _>>(),
+    };
+
+    assert_eq!(
+      manifest.to_yaml(),
+      r#"apiVersion: bitnami.com/v1alpha1
+kind: SealedSecret
+metadata:
+  annotations:
+    sealedsecrets.bitnami.com/namespace-wide: "true"
+  name: db-credentials
+  namespace: payments
+spec:
+  encryptedData:
+    "2fa.code": AgCbLy0Bkj6Mhxl3Bw==
+    password: AgBy3i4OJSWK+PiTySYZZA==
+    "true": AgAKAoiQm7QDVeXf3w==
+  template:
+    metadata:
+      annotations:
+        sealedsecrets.bitnami.com/namespace-wide: "true"
+      name: db-credentials
+      namespace: payments
+    type: Opaque
+"#
+    );
+  }
+
+  #[test]
+  fn read_the_sealed_secret() {
+    let manifest = SealedSecretManifest::from_yaml(KUBESEAL_MANIFEST).unwrap();
+
+    assert_eq!(manifest.name, "db-credentials");
+    assert_eq!(manifest.namespace, "payments");
+    assert_eq!(
+      manifest
+        .annotations
+        .get("sealedsecrets.bitnami.com/cluster-wide")
+        .map(String::as_str),
+      Some("true")
+    );
+    assert_eq!(manifest.secret_type, "kubernetes.io/dockerconfigjson");
+    assert_eq!(
+      manifest.encrypted_data.keys().map(String::as_str).collect::<Vec<_>>(),
+      vec!["2fa.code", "password"]
+    );
+    assert!(SealedSecretManifest::from_yaml("kind: Secret\nmetadata:\n  name: db-credentials\n").is_err());
+  }
+
+  #[test]
+  fn merge_the_ciphertexts() {
+    let ciphertexts = vec![
+      ("password".to_string(), "AgRotated==".to_string()),
+      ("api-key".to_string(), "AgAdded==".to_string()),
+    ]
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+
+    let merged = merge_encrypted_data(KUBESEAL_MANIFEST, &ciphertexts, &["2fa.code".to_string()]).unwrap();
+
+    assert_eq!(
+      merged,
+      KUBESEAL_MANIFEST.replace(
+        "    \"2fa.code\": AgCbLy0Bkj6Mhxl3Bw==\n    password: AgBy3i4OJSWK+PiTySYZZA==\n",
+        "    api-key: AgAdded==\n    password: AgRotated==\n"
+      )
+    );
+    let removed = merge_encrypted_data(&merged, &BTreeMap::new(), &["api-key".to_string(), "password".to_string()]).unwrap();
+    assert!(removed.contains("  encryptedData: {}\n  template:\n"), "{}", removed);
+    let restored = merge_encrypted_data(&removed, &ciphertexts, &[]).unwrap();
+    assert!(restored.contains("  encryptedData:\n    api-key: AgAdded==\n    password: AgRotated==\n  template:\n"));
+  }
+
+  #[test]
+  fn reject_the_multi_line_ciphertexts() {
+    let folded = KUBESEAL_MANIFEST.replace("password: AgBy3i4OJSWK+PiTySYZZA==", "password: >-\n      AgBy3i4OJSWK+PiTySYZZA==");
+
+    assert!(merge_encrypted_data(&folded, &BTreeMap::new(), &[]).is_err());
+  }
+}
@@ -0,0 +1,6 @@
+pub mod certificates;
+pub mod client;
+pub mod manifest;
+
+pub use certificates::{CachedCertificateProvider, CertificateLoader};
+pub use client::NativeSealedSecretClient;
@@ -0,0 +1,48 @@
+use git2::ErrorCode;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VersionControlError {
+  #[error("Error fetching the repository {0}: {1}")]
+  Fetch(String, #[source] git2::Error),
+
+  #[error("Error opening the repository {0}: {1}")]
+  Open(String, #[source] git2::Error),
+
+  #[error("Error preparing the mirror {0}: {1}")]
+  Mirror(String, #[source] git2::Error),
+
+  #[error("Error creating the worktree {0}: {1}")]
+  Worktree(String, #[source] git2::Error),
+
+  #[error("Error committing the changes: {0}")]
+  Commit(#[source] git2::Error),
+
+  #[error("Error pushing the branch {0}: {1}")]
+  Push(String, #[source] git2::Error),
+
+  #[error("Push of {0} rejected by the remote: {1}")]
+  PushRejected(String, String),
+
+  #[error("Error rebasing the changes onto {0}: {1}")]
+  Rebase(String, #[source] git2::Error),
+
+  #[error("Error creating a workspace in {0}: {1}")]
+  Workspace(String, #[source] std::io::Error),
+
+  #[error("The git operation was interrupted: {0}")]
+  Interrupted(#[source] tokio::task::JoinError),
+}
+
+impl VersionControlError {
+  /// Whether the push was rejected because the remote branch has commits missing in the local one
+  pub fn is_non_fast_forward(&self) -> bool {
+    match self {
+      VersionControlError::Push(_, err) => err.code() == ErrorCode::NotFastForward,
+      VersionControlError::PushRejected(_, reason) => ["fast-forward", "fetch first", "stale info"]
+        .iter()
+        .any(|rejection| reason.contains(rejection)),
+      _ => false,
+    }
+  }
+}
@@ -0,0 +1,723 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use git2::{
+  BranchType, Cred, ErrorCode, FetchOptions, IndexAddOption, Oid, PushOptions, RemoteCallbacks, Repository, Signature, WorktreeAddOptions,
+  WorktreePruneOptions,
+};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+use crate::domain::errors::ConflictError;
+use crate::domain::model::{CommitDto, GitOpsConfig, Workspace};
+use crate::domain::ports::outgoing::VersionControl;
+use crate::infrastructure::version_control::VersionControlError;
+
+const REMOTE_NAME: &str = "origin";
+const WORKTREE_PREFIX: &str = "workspace-";
+/// Pushes to the base branch attempted before giving up when other changes keep landing first
+const MAX_PUSH_ATTEMPTS: u32 = 5;
+/// Delay before the first rebase, doubled on every retry
+const PUSH_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// VersionControl adapter running every git operation in-process through libgit2, in the blocking threads of the runtime.
+///
+/// The repository is kept as a bare mirror in the `mirror_folder`, fetched incrementally before each operation.
+/// Every operation gets its own worktree of the mirror, removed when the workspace is dropped.
+/// The clones of the adapter share the mirror and the limit of concurrent operations.
+#[derive(Clone)]
+pub struct Git2VersionControl {
+  config: GitOpsConfig,
+  permits: Arc<Semaphore>,
+  /// Worktrees in use, only locked while the set is read or changed
+  worktrees: Arc<Mutex<HashSet<String>>>,
+  /// Serializes the fetches and the new worktrees of the mirror, without holding back the removal of the finished ones
+  mirror_lock: Arc<Mutex<()>>,
+}
+
+impl Git2VersionControl {
+  pub fn new(config: GitOpsConfig) -> Self {
+    let permits = Arc::new(Semaphore::new(config.max_concurrent_operations));
+    Self {
+      config,
+      permits,
+      worktrees: Arc::new(Mutex::new(HashSet::new())),
+      mirror_lock: Arc::new(Mutex::new(())),
+    }
+  }
+
+  fn lock_mirror(&self) -> MutexGuard<'_, ()> {
+    self.mirror_lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+
+  fn worktrees_in_use(&self) -> HashSet<String> {
+    self.worktrees.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+  }
+
+  fn remote_callbacks(&self) -> RemoteCallbacks<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    if let Some(token) = &self.config.token {
+      let username = self.config.username.clone().unwrap_or_else(|| self.config.author_name.clone());
+      callbacks.credentials(move |_url, _username_from_url, _allowed_types| Cred::userpass_plaintext(&username, token));
+    }
+    callbacks
+  }
+
+  fn open(repository_path: &str) -> Result<Repository, VersionControlError> {
+    Repository::open(repository_path).map_err(|err| VersionControlError::Open(repository_path.to_string(), err))
+  }
+
+  fn mirror_path(&self) -> PathBuf {
+    Path::new(&self.config.mirror_folder).join(format!("{}.git", self.config.repository_name))
+  }
+
+  /// Opens the bare mirror of the repository, creating it the first time
+  fn open_mirror(&self) -> Result<Repository, VersionControlError> {
+    let mirror_path = self.mirror_path();
+    let mirror_error = |err| VersionControlError::Mirror(mirror_path.to_string_lossy().into_owned(), err);
+    let mirror = match mirror_path.exists() {
+      true => Repository::open_bare(&mirror_path).map_err(mirror_error)?,
+      false => {
+        info!("Creating the mirror of {} in {:?}", self.config.repository_url, mirror_path);
+        Repository::init_bare(&mirror_path).map_err(mirror_error)?
+      }
+    };
+
+    let url = self.config.repository_url.as_str();
+    let remote_url = mirror.find_remote(REMOTE_NAME).ok().map(|remote| remote.url().map(String::from));
+    match remote_url {
+      Some(Some(remote_url)) if remote_url == url => Ok(()),
+      Some(_) => mirror.remote_set_url(REMOTE_NAME, url).map(|_| ()),
+      // Only the base branch is tracked, so the branches pushed for the Pull Requests don't pile up in the mirror
+      None => mirror.remote_with_fetch(REMOTE_NAME, url, &self.base_refspec()).map(|_| ()),
+    }
+    .map_err(mirror_error)?;
+    Ok(mirror)
+  }
+
+  fn base_refspec(&self) -> String {
+    format!("+refs/heads/{0}:refs/remotes/{1}/{0}", self.config.branch, REMOTE_NAME)
+  }
+
+  /// Fetches the new commits of the base branch into the mirror (or one of its worktrees). Returns its last commit.
+  fn fetch(&self, mirror: &Repository) -> Result<Oid, VersionControlError> {
+    let fetch_error = |err| VersionControlError::Fetch(self.config.repository_url.clone(), err);
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(self.remote_callbacks());
+    mirror
+      .find_remote(REMOTE_NAME)
+      .and_then(|mut remote| remote.fetch(&[self.base_refspec().as_str()], Some(&mut fetch_options), None))
+      .map_err(fetch_error)?;
+    mirror
+      .refname_to_id(&format!("refs/remotes/{}/{}", REMOTE_NAME, self.config.branch))
+      .map_err(fetch_error)
+  }
+
+  /// Removes the worktrees (and their branches) not in use, left behind by a crash or a previous run
+  fn collect_garbage(mirror: &Repository, worktrees_in_use: &HashSet<String>) {
+    let worktrees = mirror
+      .worktrees()
+      .map(|names| names.iter().flatten().map(String::from).collect::<Vec<String>>())
+      .unwrap_or_default();
+    let branches = mirror
+      .branches(Some(BranchType::Local))
+      .map(|branches| {
+        branches
+          .flatten()
+          .filter_map(|(branch, _)| branch.name().ok().flatten().map(String::from))
+          .filter(|name| name.starts_with(WORKTREE_PREFIX))
+          .collect::<Vec<String>>()
+      })
+      .unwrap_or_default();
+
+    let stale = worktrees
+      .into_iter()
+      .chain(branches)
+      .filter(|name| !worktrees_in_use.contains(name))
+      .collect::<HashSet<String>>();
+    for name in stale {
+      info!("Removing the stale worktree {}", name);
+      Self::remove_worktree(mirror, &name);
+    }
+  }
+
+  fn remove_worktree(mirror: &Repository, name: &str) {
+    if let Ok(worktree) = mirror.find_worktree(name) {
+      let pruned = worktree.prune(Some(WorktreePruneOptions::new().valid(true).locked(true).working_tree(true)));
+      if let Err(err) = pruned {
+        warn!("Error pruning the worktree {}: {}", name, err);
+      }
+    }
+    if let Ok(mut branch) = mirror.find_branch(name, BranchType::Local) {
+      if let Err(err) = branch.delete() {
+        warn!("Error deleting the branch of the worktree {}: {}", name, err);
+      }
+    }
+  }
+
+  /// Checks out the last commit of the base branch in a new worktree inside `folder`
+  fn add_worktree(&self, folder: &TempDir) -> Result<(String, PathBuf), VersionControlError> {
+    let _mirror_lock = self.lock_mirror();
+    let mirror = self.open_mirror()?;
+    // The worktrees are only added while the mirror is locked, so none of them can be added in between
+    Self::collect_garbage(&mirror, &self.worktrees_in_use());
+    let head = self.fetch(&mirror)?;
+
+    let folder_name = folder.path().file_name().unwrap_or_default().to_string_lossy();
+    let name = format!("{}{}", WORKTREE_PREFIX, folder_name.trim_start_matches('.'));
+    let worktree_path = folder.path().join(&self.config.repository_name);
+    let worktree_error = |err| VersionControlError::Worktree(name.clone(), err);
+
+    let commit = mirror.find_commit(head).map_err(worktree_error)?;
+    let branch = mirror.branch(&name, &commit, true).map_err(worktree_error)?;
+    let mut options = WorktreeAddOptions::new();
+    options.reference(Some(branch.get()));
+    mirror.worktree(&name, &worktree_path, Some(&options)).map_err(worktree_error)?;
+
+    self
+      .worktrees
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .insert(name.clone());
+    Ok((name, worktree_path))
+  }
+
+  /// Stages every change of the working tree (including deletions) and commits it on top of HEAD
+  fn commit_all(&self, repository: &Repository, message: &str) -> Result<Oid, VersionControlError> {
+    let mut index = repository.index().map_err(VersionControlError::Commit)?;
+    index
+      .add_all(["*"].iter(), IndexAddOption::DEFAULT, None)
+      .and_then(|_| index.update_all(["*"].iter(), None))
+      .and_then(|_| index.write())
+      .map_err(VersionControlError::Commit)?;
+
+    let tree = index
+      .write_tree()
+      .and_then(|tree_id| repository.find_tree(tree_id))
+      .map_err(VersionControlError::Commit)?;
+    let parent = repository
+      .head()
+      .and_then(|head| head.peel_to_commit())
+      .map_err(VersionControlError::Commit)?;
+    let signature = self.signature().map_err(VersionControlError::Commit)?;
+
+    repository
+      .commit(Some("HEAD"), &signature, &signature, message, &tree, &[&parent])
+      .map_err(VersionControlError::Commit)
+  }
+
+  fn signature(&self) -> Result<Signature<'static>, git2::Error> {
+    Signature::now(&self.config.author_name, &self.config.author_email)
+  }
+
+  /// Fetches the base branch and replays the local commits on top of it. Returns the new HEAD.
+  ///
+  /// The rebase is aborted, leaving the worktree untouched, if the changes conflict with the remote ones.
+  fn rebase(&self, repository: &Repository) -> Result<Oid> {
+    let upstream = {
+      let _mirror_lock = self.lock_mirror();
+      self.fetch(repository)?
+    };
+    let branch = &self.config.branch;
+    let rebase_error = |err| VersionControlError::Rebase(branch.clone(), err);
+
+    let upstream = repository.find_annotated_commit(upstream).map_err(rebase_error)?;
+    let signature = self.signature().map_err(rebase_error)?;
+    let mut rebase = repository.rebase(None, Some(&upstream), None, None).map_err(rebase_error)?;
+    let mut conflicts = false;
+    let replayed = (|| {
+      while let Some(operation) = rebase.next() {
+        operation?;
+        if repository.index()?.has_conflicts() {
+          conflicts = true;
+          return Ok(());
+        }
+        match rebase.commit(None, &signature, None) {
+          // The change is already in the remote branch
+          Err(err) if err.code() == ErrorCode::Applied => {}
+          result => result.map(|_| ())?,
+        }
+      }
+      rebase.finish(Some(&signature))
+    })();
+
+    if conflicts || replayed.is_err() {
+      if let Err(err) = rebase.abort() {
+        warn!("Error aborting the rebase onto {}: {}", branch, err);
+      }
+    }
+    replayed.map_err(rebase_error)?;
+    if conflicts {
+      return Err(ConflictError(format!("The changes conflict with the last commits of the branch {}", branch)).into());
+    }
+
+    repository
+      .head()
+      .and_then(|head| head.peel_to_commit())
+      .map(|commit| commit.id())
+      .map_err(rebase_error)
+      .map_err(anyhow::Error::from)
+  }
+
+  /// Pushes the current branch of the worktree to the given branch of the remote
+  fn push(&self, repository: &Repository, branch: &str) -> Result<(), VersionControlError> {
+    let local_branch = Self::current_branch(repository)?;
+    let refspec = format!("refs/heads/{}:refs/heads/{}", local_branch, branch);
+    let mut rejection: Option<String> = None;
+    {
+      let mut callbacks = self.remote_callbacks();
+      callbacks.push_update_reference(|_refname, status| {
+        rejection = status.map(String::from);
+        Ok(())
+      });
+      let mut push_options = PushOptions::new();
+      push_options.remote_callbacks(callbacks);
+
+      repository
+        .find_remote(REMOTE_NAME)
+        .and_then(|mut remote| remote.push(&[refspec.as_str()], Some(&mut push_options)))
+        .map_err(|err| VersionControlError::Push(branch.to_string(), err))?;
+    }
+
+    match rejection {
+      Some(reason) => Err(VersionControlError::PushRejected(branch.to_string(), reason)),
+      None => Ok(()),
+    }
+  }
+
+  fn current_branch(repository: &Repository) -> Result<String, VersionControlError> {
+    repository
+      .head()
+      .map_err(VersionControlError::Commit)
+      .map(|head| head.shorthand().unwrap_or_default().to_string())
+  }
+
+  /// Creates the worktree of a new workspace, released with the permit when the lease is dropped
+  fn lease_worktree(&self, permit: OwnedSemaphorePermit) -> Result<(PathBuf, WorktreeLease), VersionControlError> {
+    let destination_folder = &self.config.destination_folder;
+    std::fs::create_dir_all(destination_folder).map_err(|err| VersionControlError::Workspace(destination_folder.clone(), err))?;
+    std::fs::create_dir_all(&self.config.mirror_folder)
+      .map_err(|err| VersionControlError::Workspace(self.config.mirror_folder.clone(), err))?;
+    let folder = TempDir::new_in(destination_folder).map_err(|err| VersionControlError::Workspace(destination_folder.clone(), err))?;
+
+    let (name, worktree_path) = self.add_worktree(&folder)?;
+    info!("Worktree {} of {} ready in {:?}", name, self.config.branch, worktree_path);
+
+    let lease = WorktreeLease {
+      name,
+      mirror_path: self.mirror_path(),
+      worktrees: self.worktrees.clone(),
+      _folder: folder,
+      _permit: permit,
+    };
+    Ok((worktree_path, lease))
+  }
+
+  /// Runs the libgit2 work on the repository of a workspace in the blocking threads of the runtime,
+  /// so its network round-trips (fetches and pushes) don't hold the async workers
+  async fn in_repository<T, E, F>(&self, repository_path: &str, work: F) -> Result<T, E>
+  where
+    T: Send + 'static,
+    E: From<VersionControlError> + Send + 'static,
+    F: FnOnce(&Self, &Repository) -> Result<T, E> + Send + 'static,
+  {
+    let git = self.clone();
+    let repository_path = repository_path.to_string();
+    tokio::task::spawn_blocking(move || work(&git, &Self::open(&repository_path)?))
+      .await
+      .map_err(VersionControlError::Interrupted)?
+  }
+
+  /// Suffix of the pushed branches: the time of the push, and a random part telling apart the ones pushed within the same second
+  fn branch_suffix() -> String {
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_secs())
+      .unwrap_or_default();
+    let random = Uuid::new_v4().to_simple().to_string();
+    format!("{}-{}", timestamp, &random[..8])
+  }
+}
+
+/// Worktree used by a single operation. Removed from the mirror, with its branch and folder, when dropped.
+struct WorktreeLease {
+  name: String,
+  mirror_path: PathBuf,
+  worktrees: Arc<Mutex<HashSet<String>>>,
+  _folder: TempDir,
+  _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for WorktreeLease {
+  fn drop(&mut self) {
+    // Only released from the set once removed, so it can't be taken for a stale worktree in between
+    match Repository::open_bare(&self.mirror_path) {
+      Ok(mirror) => Git2VersionControl::remove_worktree(&mirror, &self.name),
+      Err(err) => warn!("Error opening the mirror to remove the worktree {}: {}", self.name, err),
+    }
+    self
+      .worktrees
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .remove(&self.name);
+  }
+}
+
+#[async_trait(?Send)]
+impl VersionControl for Git2VersionControl {
+  async fn clone_repo(&self) -> Result<Workspace> {
+    let permit = self
+      .permits
+      .clone()
+      .acquire_owned()
+      .await
+      .context("The git operations are no longer accepted")?;
+
+    let git = self.clone();
+    let (worktree_path, lease) = tokio::task::spawn_blocking(move || git.lease_worktree(permit))
+      .await
+      .map_err(VersionControlError::Interrupted)??;
+    Ok(Workspace::new(worktree_path.to_string_lossy().into_owned(), lease))
+  }
+
+  async fn auto_commit(&self, workspace: &Workspace, commit_msg: String) -> Result<CommitDto> {
+    let branch = self.config.branch.clone();
+    info!("Auto-commit enabled. Pushing all the changes to {}", branch);
+
+    let mut sha = self
+      .in_repository(workspace.path(), move |git, repository| git.commit_all(repository, &commit_msg))
+      .await?;
+    let mut attempt = 1;
+    loop {
+      let target = branch.clone();
+      let pushed = self
+        .in_repository(workspace.path(), move |git, repository| git.push(repository, &target))
+        .await;
+      match pushed {
+        Ok(()) => break,
+        Err(err) if err.is_non_fast_forward() && attempt < MAX_PUSH_ATTEMPTS => {
+          let delay = PUSH_RETRY_DELAY * 2u32.pow(attempt - 1);
+          warn!(
+            "{}. Rebasing onto the remote branch in {:?} ({}/{})",
+            err,
+            delay,
+            attempt,
+            MAX_PUSH_ATTEMPTS - 1
+          );
+          tokio::time::sleep(delay).await;
+          sha = self
+            .in_repository(workspace.path(), |git, repository| git.rebase(repository))
+            .await?;
+          attempt += 1;
+        }
+        Err(err) if err.is_non_fast_forward() => {
+          return Err(ConflictError(format!("{} after {} attempts", err, MAX_PUSH_ATTEMPTS)).into());
+        }
+        Err(err) => return Err(err.into()),
+      }
+    }
+    Ok(CommitDto {
+      branch,
+      sha: sha.to_string(),
+    })
+  }
+
+  async fn push_branch(&self, workspace: &Workspace, commit_msg: String, branch_name: String) -> Result<CommitDto> {
+    let branch = format!("{}-{}", branch_name, Self::branch_suffix());
+
+    let target = branch.clone();
+    let sha = self
+      .in_repository(workspace.path(), move |git, repository| {
+        let sha = git.commit_all(repository, &commit_msg)?;
+        git.push(repository, &target).map(|_| sha)
+      })
+      .await?;
+    info!("Branch {} pushed", branch);
+    Ok(CommitDto {
+      branch,
+      sha: sha.to_string(),
+    })
+  }
+}
+
+#[cfg(test)]
+pub mod tests {
+  use git2::{Oid, Repository, Signature};
+  use std::path::{Path, PathBuf};
+  use std::time::Duration;
+  use tempfile::TempDir;
+  use tokio::time::timeout;
+
+  use crate::domain::errors::ConflictError;
+  use crate::domain::model::{GitOpsConfig, GitProvider};
+  use crate::domain::ports::outgoing::VersionControl;
+  use crate::infrastructure::version_control::Git2VersionControl;
+
+  const BRANCH: &str = "dev";
+
+  /// Creates a bare repository with a single commit in the `dev` branch to be used as the remote
+  pub fn bare_remote(folder: &Path) -> PathBuf {
+    let remote_path = folder.join("remote.git");
+    let remote = Repository::init_bare(&remote_path).unwrap();
+    let signature = Signature::now("test", "test@example.com").unwrap();
+    let blob = remote.blob(b"# GitOps\n").unwrap();
+    let mut tree_builder = remote.treebuilder(None).unwrap();
+    tree_builder.insert("README.md", blob, 0o100644).unwrap();
+    let tree = remote.find_tree(tree_builder.write().unwrap()).unwrap();
+    remote
+      .commit(
+        Some(&format!("refs/heads/{}", BRANCH)),
+        &signature,
+        &signature,
+        "Initial commit",
+        &tree,
+        &[],
+      )
+      .unwrap();
+    remote.set_head(&format!("refs/heads/{}", BRANCH)).unwrap();
+    remote_path
+  }
+
+  pub fn config(remote_path: &Path, destination_folder: &Path) -> GitOpsConfig {
+    GitOpsConfig {
+      repository_name: "gitops".to_string(),
+      organization: "test".to_string(),
+      branch: BRANCH.to_string(),
+      destination_folder: destination_folder.to_string_lossy().into_owned(),
+      mirror_folder: destination_folder.with_file_name("mirror").to_string_lossy().into_owned(),
+      repository_path: "test/gitops".to_string(),
+      repository_url: remote_path.to_string_lossy().into_owned(),
+      username: None,
+      token: None,
+      author_name: "test".to_string(),
+      author_email: "test@example.com".to_string(),
+      provider: GitProvider::GitHub,
+      api_url: "http://localhost".to_string(),
+      max_concurrent_operations: 4,
+    }
+  }
+
+  fn mirror(folder: &Path) -> Repository {
+    Repository::open_bare(folder.join("mirror").join("gitops.git")).unwrap()
+  }
+
+  fn last_commit(remote_path: &Path, reference: &str) -> (String, String) {
+    let remote = Repository::open_bare(remote_path).unwrap();
+    let commit = remote.find_reference(reference).unwrap().peel_to_commit().unwrap();
+    (commit.id().to_string(), commit.message().unwrap().to_string())
+  }
+
+  #[tokio::test]
+  async fn clone_commit_and_push_to_the_base_branch() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let workspace = git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(workspace.path()).join("nodegroup.yaml"), "kind: NodeGroup\n").unwrap();
+    let commit = git.auto_commit(&workspace, "Add nodegroup".into()).await.unwrap();
+
+    assert_eq!(commit.branch, "dev");
+    assert_eq!(
+      last_commit(&remote_path, "refs/heads/dev"),
+      (commit.sha, "Add nodegroup".to_string())
+    );
+  }
+
+  #[tokio::test]
+  async fn push_a_new_branch_for_pull_requests() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let workspace = git.clone_repo().await.unwrap();
+    std::fs::remove_file(Path::new(workspace.path()).join("README.md")).unwrap();
+    let commit = git
+      .push_branch(&workspace, "Remove readme".into(), "remove-readme".into())
+      .await
+      .unwrap();
+
+    assert!(commit.branch.starts_with("remove-readme-"));
+    assert_eq!(
+      last_commit(&remote_path, &format!("refs/heads/{}", commit.branch)),
+      (commit.sha, "Remove readme".to_string())
+    );
+    assert_eq!(last_commit(&remote_path, "refs/heads/dev").1, "Initial commit");
+  }
+
+  #[tokio::test]
+  async fn push_distinct_branches_with_the_same_name() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let first = git.clone_repo().await.unwrap();
+    let second = git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(first.path()).join("nodegroup.yaml"), "kind: NodeGroup\n").unwrap();
+    std::fs::write(Path::new(second.path()).join("nodegroup.yaml"), "kind: NodeGroup\n").unwrap();
+    let first_commit = git
+      .push_branch(&first, "Add nodegroup".into(), "add-nodegroup".into())
+      .await
+      .unwrap();
+    let second_commit = git
+      .push_branch(&second, "Add nodegroup".into(), "add-nodegroup".into())
+      .await
+      .unwrap();
+
+    assert_ne!(first_commit.branch, second_commit.branch);
+    assert_eq!(
+      last_commit(&remote_path, &format!("refs/heads/{}", first_commit.branch)).0,
+      first_commit.sha
+    );
+    assert_eq!(
+      last_commit(&remote_path, &format!("refs/heads/{}", second_commit.branch)).0,
+      second_commit.sha
+    );
+  }
+
+  #[tokio::test]
+  async fn rebase_and_retry_when_the_branch_moved() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let first = git.clone_repo().await.unwrap();
+    let second = git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(first.path()).join("nodegroup.yaml"), "kind: NodeGroup\n").unwrap();
+    let first_commit = git.auto_commit(&first, "Add nodegroup".into()).await.unwrap();
+    std::fs::write(Path::new(second.path()).join("secret.yaml"), "kind: SealedSecret\n").unwrap();
+    let second_commit = git.auto_commit(&second, "Add secret".into()).await.unwrap();
+
+    assert_eq!(
+      last_commit(&remote_path, "refs/heads/dev"),
+      (second_commit.sha.clone(), "Add secret".to_string())
+    );
+    let remote = Repository::open_bare(&remote_path).unwrap();
+    let pushed = remote.find_commit(Oid::from_str(&second_commit.sha).unwrap()).unwrap();
+    assert_eq!(pushed.parent_id(0).unwrap().to_string(), first_commit.sha);
+    assert!(pushed.tree().unwrap().get_name("nodegroup.yaml").is_some());
+  }
+
+  #[tokio::test]
+  async fn report_a_conflict_when_the_rebase_fails() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let first = git.clone_repo().await.unwrap();
+    let second = git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(first.path()).join("README.md"), "# First\n").unwrap();
+    let first_commit = git.auto_commit(&first, "First change".into()).await.unwrap();
+    std::fs::write(Path::new(second.path()).join("README.md"), "# Second\n").unwrap();
+    let error = git.auto_commit(&second, "Second change".into()).await.unwrap_err();
+
+    assert!(error.downcast_ref::<ConflictError>().is_some());
+    assert_eq!(last_commit(&remote_path, "refs/heads/dev").0, first_commit.sha);
+  }
+
+  #[tokio::test]
+  async fn clone_fails_with_an_unknown_remote() {
+    let folder = TempDir::new().unwrap();
+    let git = Git2VersionControl::new(config(&folder.path().join("missing.git"), &folder.path().join("workspaces")));
+
+    assert!(git.clone_repo().await.is_err());
+  }
+
+  #[tokio::test]
+  async fn isolate_and_remove_the_workspaces() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let workspaces = folder.path().join("workspaces");
+    let git = Git2VersionControl::new(config(&remote_path, &workspaces));
+
+    let first = git.clone_repo().await.unwrap();
+    let second = git.clone_repo().await.unwrap();
+    assert_ne!(first.path(), second.path());
+    assert!(Path::new(first.path()).join("README.md").exists());
+
+    drop(first);
+    drop(second);
+    assert_eq!(std::fs::read_dir(&workspaces).unwrap().count(), 0);
+    assert_eq!(mirror(folder.path()).worktrees().unwrap().len(), 0);
+  }
+
+  #[tokio::test]
+  async fn remove_the_workspaces_while_the_mirror_is_locked() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let workspace = git.clone_repo().await.unwrap();
+    let _fetch = git.lock_mirror();
+    drop(workspace);
+
+    assert_eq!(mirror(folder.path()).worktrees().unwrap().len(), 0);
+    assert!(git.worktrees_in_use().is_empty());
+  }
+
+  #[tokio::test]
+  async fn fetch_the_new_commits_into_the_mirror() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(config(&remote_path, &folder.path().join("workspaces")));
+
+    let workspace = git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(workspace.path()).join("nodegroup.yaml"), "kind: NodeGroup\n").unwrap();
+    git.auto_commit(&workspace, "Add nodegroup".into()).await.unwrap();
+    drop(workspace);
+
+    let other_git = Git2VersionControl::new(config(&remote_path, &folder.path().join("other-workspaces")));
+    let workspace = other_git.clone_repo().await.unwrap();
+    std::fs::write(Path::new(workspace.path()).join("secret.yaml"), "kind: SealedSecret\n").unwrap();
+    other_git.auto_commit(&workspace, "Add secret".into()).await.unwrap();
+    drop(workspace);
+
+    let workspace = git.clone_repo().await.unwrap();
+    assert!(Path::new(workspace.path()).join("nodegroup.yaml").exists());
+    assert!(Path::new(workspace.path()).join("secret.yaml").exists());
+  }
+
+  #[tokio::test]
+  async fn remove_the_stale_worktrees() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let workspaces = folder.path().join("workspaces");
+
+    let leaked = Git2VersionControl::new(config(&remote_path, &workspaces))
+      .clone_repo()
+      .await
+      .unwrap();
+    let leaked_path = leaked.path().to_string();
+    std::mem::forget(leaked);
+    assert_eq!(mirror(folder.path()).worktrees().unwrap().len(), 1);
+
+    let git = Git2VersionControl::new(config(&remote_path, &workspaces));
+    let workspace = git.clone_repo().await.unwrap();
+
+    let worktrees = mirror(folder.path()).worktrees().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert!(!Path::new(&leaked_path).exists());
+    assert!(Path::new(workspace.path()).join("README.md").exists());
+  }
+
+  #[tokio::test]
+  async fn limit_the_concurrent_operations() {
+    let folder = TempDir::new().unwrap();
+    let remote_path = bare_remote(folder.path());
+    let git = Git2VersionControl::new(GitOpsConfig {
+      max_concurrent_operations: 1,
+      ..config(&remote_path, &folder.path().join("workspaces"))
+    });
+
+    let workspace = git.clone_repo().await.unwrap();
+    assert!(timeout(Duration::from_millis(200), git.clone_repo()).await.is_err());
+
+    drop(workspace);
+    assert!(timeout(Duration::from_secs(5), git.clone_repo()).await.unwrap().is_ok());
+  }
+}
@@ -1 +1,5 @@
-pub mod git;
+pub mod errors;
+pub mod libgit2;
+
+pub use errors::VersionControlError;
+pub use libgit2::Git2VersionControl;
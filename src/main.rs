@@ -8,24 +8,35 @@ mod application;
 mod domain;
 mod infrastructure;
 mod utils;
-use actix_web::{middleware, web::Data, App, HttpServer};
+use actix_web::{
+  middleware,
+  web::{Data, JsonConfig},
+  App, HttpServer,
+};
 use anyhow::{Context, Result};
 use kube::client::Client;
+use kube::config::KubeConfigOptions;
+use std::time::Duration;
 mod env_config;
 
-use crate::domain::model::InstanceType;
+use crate::application::api::clusters::Clusters;
+use crate::domain::model::{ClusterConfig, InstanceType, ManifestLayout, SecretsSettings};
 use crate::domain::ports::incoming::ScheduledService;
 use crate::domain::ports::outgoing::WriteStore;
 use crate::domain::services::probes::DefaultProbesService;
 use crate::domain::services::{
-  DefaultInstanceTypesService, DefaultInstanceTypesUpdater, DefaultNodegroupsService, DefaultSecretsService, DefaultTemplateService,
-  ScheduledInstanceTypesService,
+  DefaultChangeRequestReconciler, DefaultChangeRequestTemplates, DefaultInstanceTypesService, DefaultInstanceTypesUpdater,
+  DefaultJobsService, DefaultNodegroupsService, DefaultSecretsService, DefaultTemplateService, ScheduledInstanceTypesService,
+  SecretSealing,
 };
 use crate::env_config::EnvConfig;
 use crate::infrastructure::datasources::FileDataSource;
-use crate::infrastructure::kubernetes::{DefaultNodegroupsRepository, DefaultSecretsRepository, KubesealClient};
+use crate::infrastructure::kubernetes::{ControllerCertificateLoader, DefaultNodegroupsRepository, DefaultSecretsRepository};
 
-use crate::infrastructure::{GitVersionControl, InMemoryStore};
+use crate::infrastructure::{
+  CachedCertificateProvider, Git2VersionControl, GitPullRequestProvider, InMemoryStore, KvChangeRequestStore, KvJobStore,
+  NativeSealedSecretClient,
+};
 
 #[inline]
 pub fn main_error<E: std::fmt::Debug>(msg: &'static str) -> Box<dyn FnOnce(E) -> std::io::Error> {
@@ -58,57 +69,120 @@ async fn async_main() -> Result<()> {
   env_logger::init();
   dotenv::dotenv().map_err(main_error("Error loading .env files"))?;
 
-  let client = Client::try_default().await.expect("create client");
-  let namespace = EnvConfig::namespace().context("Error retrieving the namespace")?;
-
-  // We initialize the repository outside the http server. Otherwise, we will create a new reflector per thread.
-  let secrets_repository = DefaultSecretsRepository::new(client.clone(), &namespace);
-  let sealed_secret_client = KubesealClient::new(None);
-  let nodegroup_repository = DefaultNodegroupsRepository::new(client.clone());
+  let registry = EnvConfig::cluster_registry()?;
 
   //Shared store for instances types
   let store_path = EnvConfig::stores_path().context("Error determining the path for the instance types key-value store")?;
-  let store = InMemoryStore::<InstanceType>::new(store_path)?;
+  let kv_store = kv::Store::new(kv::Config::new(&store_path)).context("Error opening the key-value store")?;
+  let store = InMemoryStore::<InstanceType>::from_store(&kv_store)?;
+
+  //Background jobs, persisted in the same key-value store
+  let job_workers = EnvConfig::job_workers()?;
+  let jobs_service = DefaultJobsService::new(KvJobStore::new(&kv_store)?, job_workers).context("Error starting the job workers")?;
 
   //Template services
   let template_service = DefaultTemplateService::new().context("Error creating templates")?;
+  let change_request_templates_path = EnvConfig::change_request_templates_path()?;
+  let change_request_templates =
+    DefaultChangeRequestTemplates::new(&change_request_templates_path).context("Error creating the change request templates")?;
 
-  //Version control
-  let gitops_config = EnvConfig::gitops_config()?;
-  let git_service = GitVersionControl::new(gitops_config.clone(), None);
+  //Kubernetes and GitOps adapters of every cluster
+  let certificate_ttl = Duration::from_secs(EnvConfig::sealing_certificate_ttl()?);
+  let mut clusters = Vec::new();
+  for cluster in &registry.clusters {
+    clusters.push(ClusterAdapters::new(cluster, &kv_store, certificate_ttl).await?);
+  }
 
   //Create the Instance tpye cron service to update the store in the background
   create_cron_for_instance_types(store.clone())?;
 
+  //Follow the submitted change requests until the clusters reflect them
+  for cluster in &clusters {
+    cluster.change_request_tracker().start()?;
+  }
+
   let _ = HttpServer::new(move || {
     let probes_service = DefaultProbesService::new();
-    // Secrets init
-    let secrets_service = DefaultSecretsService::new(secrets_repository.clone(), sealed_secret_client.clone(), git_service.clone());
+    let mut secrets_services = Clusters::new(&registry.default_cluster);
+    let mut nodegroup_services = Clusters::new(&registry.default_cluster);
+    let mut change_request_trackers = Clusters::new(&registry.default_cluster);
+    for cluster in &clusters {
+      // Secrets init
+      let secrets_service = DefaultSecretsService::new(
+        cluster.secrets_repository.clone(),
+        SecretSealing {
+          client: cluster.sealed_secret_client.clone(),
+          certificate_provider: cluster.certificate_provider.clone(),
+        },
+        template_service.clone(),
+        cluster.git_service.clone(),
+        cluster.pull_request_provider.clone(),
+        change_request_templates.clone(),
+        &cluster.secrets_settings,
+      );
+      secrets_services.insert(&cluster.id, secrets_service);
+
+      // Nodegroups
+      let nodegroup_service = DefaultNodegroupsService::new(
+        cluster.nodegroup_repository.clone(),
+        store.clone(),
+        template_service.clone(),
+        cluster.git_service.clone(),
+        cluster.pull_request_provider.clone(),
+        change_request_templates.clone(),
+        cluster.layout.clone(),
+      );
+      nodegroup_services.insert(&cluster.id, nodegroup_service);
+
+      //Change requests
+      change_request_trackers.insert(&cluster.id, cluster.change_request_tracker());
+    }
 
     //Instance types
     let instance_types_service = DefaultInstanceTypesService::new(store.clone());
 
-    // Nodegroups
-    let nodegroup_service = DefaultNodegroupsService::new(
-      nodegroup_repository.clone(),
-      store.clone(),
-      template_service.clone(),
-      git_service.clone(),
-    );
-
     App::new()
-      .app_data(Data::new(secrets_service))
-      .app_data(Data::new(nodegroup_service))
+      .app_data(Data::new(secrets_services))
+      .app_data(Data::new(nodegroup_services))
       .app_data(Data::new(probes_service))
       .app_data(Data::new(instance_types_service))
+      .app_data(Data::new(jobs_service.clone()))
+      .app_data(Data::new(change_request_trackers))
+      .app_data(Data::new(registry.clone()))
+      .app_data(JsonConfig::default().error_handler(application::api::errors::json_error_handler))
       .wrap(middleware::Logger::default())
       .configure(application::api::probes::routes::<DefaultProbesService>)
+      .configure(application::api::clusters::routes)
       .configure(
         application::api::nodegroups::routes::<
-          DefaultNodegroupsService<DefaultNodegroupsRepository, InMemoryStore<InstanceType>, DefaultTemplateService, GitVersionControl>,
+          DefaultNodegroupsService<
+            DefaultNodegroupsRepository,
+            InMemoryStore<InstanceType>,
+            DefaultTemplateService,
+            Git2VersionControl,
+            GitPullRequestProvider,
+            DefaultChangeRequestTemplates,
+          >,
+          DefaultJobsService<KvJobStore>,
+          ChangeRequestReconciler,
         >,
       )
-      .configure(application::api::secrets::routes::<DefaultSecretsService<DefaultSecretsRepository, KubesealClient, GitVersionControl>>)
+      .configure(
+        application::api::secrets::routes::<
+          DefaultSecretsService<
+            DefaultSecretsRepository,
+            NativeSealedSecretClient,
+            CachedCertificateProvider<ControllerCertificateLoader>,
+            DefaultTemplateService,
+            Git2VersionControl,
+            GitPullRequestProvider,
+            DefaultChangeRequestTemplates,
+          >,
+          DefaultJobsService<KvJobStore>,
+          ChangeRequestReconciler,
+        >,
+      )
+      .configure(application::api::jobs::routes::<DefaultJobsService<KvJobStore>>)
       .configure(application::api::instance_types::routes::<DefaultInstanceTypesService<InMemoryStore<InstanceType>>>)
   })
   .workers(10)
@@ -121,6 +195,74 @@ async fn async_main() -> Result<()> {
   Ok(())
 }
 
+type ChangeRequestReconciler = DefaultChangeRequestReconciler<
+  DefaultNodegroupsRepository,
+  DefaultSecretsRepository,
+  GitPullRequestProvider,
+  KvChangeRequestStore<'static>,
+>;
+
+/// Adapters reaching a cluster and its GitOps repository, shared by the HTTP workers
+#[derive(Clone)]
+struct ClusterAdapters {
+  id: String,
+  layout: ManifestLayout,
+  secrets_settings: SecretsSettings,
+  nodegroup_repository: DefaultNodegroupsRepository,
+  secrets_repository: DefaultSecretsRepository,
+  sealed_secret_client: NativeSealedSecretClient,
+  certificate_provider: CachedCertificateProvider<ControllerCertificateLoader>,
+  git_service: Git2VersionControl,
+  pull_request_provider: GitPullRequestProvider,
+  change_request_store: KvChangeRequestStore<'static>,
+}
+
+impl ClusterAdapters {
+  // We initialize the repositories outside the http server. Otherwise, we will create new reflectors per thread.
+  async fn new(cluster: &ClusterConfig, kv_store: &kv::Store, certificate_ttl: Duration) -> Result<Self> {
+    let client = match &cluster.kube_context {
+      Some(context) => {
+        let options = KubeConfigOptions {
+          context: Some(context.clone()),
+          ..KubeConfigOptions::default()
+        };
+        let config = kube::Config::from_kubeconfig(&options)
+          .await
+          .with_context(|| format!("Error loading the kubeconfig context {}", context))?;
+        Client::try_from(config)?
+      }
+      None => Client::try_default().await.context("Error creating the Kubernetes client")?,
+    };
+    Ok(Self {
+      id: cluster.id.clone(),
+      layout: cluster.layout.clone(),
+      secrets_settings: SecretsSettings {
+        layout: cluster.layout.clone(),
+        sealing_policy: cluster.sealing_policy(),
+      },
+      nodegroup_repository: DefaultNodegroupsRepository::new(client.clone()),
+      secrets_repository: DefaultSecretsRepository::new(client.clone(), &cluster.namespace),
+      sealed_secret_client: NativeSealedSecretClient::new(),
+      certificate_provider: CachedCertificateProvider::new(
+        ControllerCertificateLoader::new(cluster.sealing_certificate.clone(), client),
+        certificate_ttl,
+      ),
+      git_service: Git2VersionControl::new(cluster.gitops.clone()),
+      pull_request_provider: GitPullRequestProvider::new(&cluster.gitops),
+      change_request_store: KvChangeRequestStore::new(kv_store, &cluster.id)?,
+    })
+  }
+
+  fn change_request_tracker(&self) -> ChangeRequestReconciler {
+    DefaultChangeRequestReconciler::new(
+      self.nodegroup_repository.clone(),
+      self.secrets_repository.clone(),
+      self.pull_request_provider.clone(),
+      self.change_request_store.clone(),
+    )
+  }
+}
+
 fn create_cron_for_instance_types<S>(store: S) -> Result<()>
 where
   S: WriteStore<InstanceType> + Clone + Send + Sync + 'static,
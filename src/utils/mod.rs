@@ -1,3 +1,5 @@
 pub mod parsers;
+pub mod time;
+pub mod validators;
 
 pub use parsers::memory;
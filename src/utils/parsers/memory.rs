@@ -1,7 +1,10 @@
 use anyhow::Result;
 use regex::{Captures, Regex};
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::num::ParseFloatError;
+use std::str::FromStr;
 use thiserror::Error;
 
 lazy_static! {
@@ -28,6 +31,16 @@ lazy_static! {
   .unwrap();
 }
 
+/// Kubernetes suffixes of the canonical quantities, from the largest
+const CANONICAL_UNITS: [(&str, usize); 6] = [
+  ("Gi", 1073741824),
+  ("Mi", 1048576),
+  ("Ki", 1024),
+  ("G", 1000000000),
+  ("M", 1000000),
+  ("k", 1000),
+];
+
 #[derive(Error, PartialEq, Debug)]
 pub enum MemoryParserError {
   #[error("Input data is invalid")]
@@ -37,7 +50,6 @@ pub enum MemoryParserError {
   ConversionError(#[source] ParseFloatError),
 }
 
-#[allow(unused)]
 pub fn parse_memory_in_bytes<S: AsRef<str>>(input: S) -> Result<usize, MemoryParserError> {
   let normalized_input = remove_whitespace(input.as_ref());
 
@@ -47,6 +59,66 @@ pub fn parse_memory_in_bytes<S: AsRef<str>>(input: S) -> Result<usize, MemoryPar
     .and_then(extract_memory_in_bytes)
 }
 
+/// Memory quantity (like `20Gi`) that is guaranteed to be parseable by `parse_memory_in_bytes`.
+/// It is (de)serialized as a string, kept in its canonical Kubernetes form: `2 GiB` or `2.Gi` become `2Gi`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct MemoryQuantity {
+  value: String,
+  bytes: usize,
+}
+
+impl MemoryQuantity {
+  pub fn bytes(&self) -> usize {
+    self.bytes
+  }
+
+  pub fn as_str(&self) -> &str {
+    self.value.as_str()
+  }
+}
+
+impl FromStr for MemoryQuantity {
+  type Err = MemoryParserError;
+
+  fn from_str(input: &str) -> Result<Self, Self::Err> {
+    parse_memory_in_bytes(input).map(|bytes| Self {
+      value: canonical_quantity(bytes),
+      bytes,
+    })
+  }
+}
+
+impl TryFrom<String> for MemoryQuantity {
+  type Error = MemoryParserError;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    value.parse()
+  }
+}
+
+impl From<MemoryQuantity> for String {
+  fn from(quantity: MemoryQuantity) -> Self {
+    quantity.value
+  }
+}
+
+impl std::fmt::Display for MemoryQuantity {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{}", self.value)
+  }
+}
+
+/// Writes the bytes with the largest Kubernetes suffix dividing them, or as a plain number of bytes
+// `usize::is_multiple_of` needs Rust 1.87, newer than the toolchain of the project
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+fn canonical_quantity(bytes: usize) -> String {
+  CANONICAL_UNITS
+    .iter()
+    .find(|(_, scale)| bytes > 0 && bytes % scale == 0)
+    .map_or_else(|| bytes.to_string(), |(unit, scale)| format!("{}{}", bytes / scale, unit))
+}
+
 fn remove_whitespace(input: &str) -> String {
   input.chars().filter(|ch| !ch.is_whitespace()).collect()
 }
@@ -77,7 +149,7 @@ fn convert_memory_to_usize(value: String, unit_scale: f32) -> Result<usize, Memo
 
 #[cfg(test)]
 mod tests {
-  use crate::utils::parsers::memory::{parse_memory_in_bytes, MemoryParserError};
+  use crate::utils::parsers::memory::{parse_memory_in_bytes, MemoryParserError, MemoryQuantity};
 
   const VALID_TEST_CASES: &[(&str, usize)] = &[
     ("2 KiB", 2048),
@@ -116,4 +188,30 @@ mod tests {
       assert_eq!(parse_memory_in_bytes(*value).unwrap_err(), *expected_output)
     }
   }
+
+  #[test]
+  fn deserialize_memory_quantities() {
+    let quantity: MemoryQuantity = serde_json::from_str(r#""2 Gi""#).unwrap();
+
+    assert_eq!(quantity.as_str(), "2Gi");
+    assert_eq!(quantity.bytes(), 2147483648);
+    assert_eq!(serde_json::to_string(&quantity).unwrap(), r#""2Gi""#);
+    assert!(serde_json::from_str::<MemoryQuantity>(r#""2 GB""#).is_err());
+  }
+
+  #[test]
+  fn normalize_memory_quantities() {
+    for (value, expected_quantity) in [
+      ("20Gi", "20Gi"),
+      ("2 GiB", "2Gi"),
+      ("2GiB", "2Gi"),
+      ("2.Gi", "2Gi"),
+      ("1.5 Gi", "1536Mi"),
+      (".5KiB", "512"),
+      ("0.5 G", "500M"),
+      ("1500Mi", "1500Mi"),
+    ] {
+      assert_eq!(value.parse::<MemoryQuantity>().unwrap().as_str(), expected_quantity, "{}", value);
+    }
+  }
 }
@@ -0,0 +1,118 @@
+use regex::Regex;
+
+/// Maximum length of a DNS-1123 label
+const DNS1123_LABEL_MAX_LENGTH: usize = 63;
+/// Maximum length of a DNS-1123 subdomain
+const DNS1123_SUBDOMAIN_MAX_LENGTH: usize = 253;
+/// Maximum length of the name of a qualified name, and of a label value
+const QUALIFIED_NAME_MAX_LENGTH: usize = 63;
+
+lazy_static! {
+  static ref DNS1123_LABEL_REGEX: Regex = Regex::new(r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$").unwrap();
+  static ref QUALIFIED_NAME_REGEX: Regex = Regex::new(r"^([A-Za-z0-9][-A-Za-z0-9_.]*)?[A-Za-z0-9]$").unwrap();
+}
+
+/// Checks if the input is a valid DNS-1123 label, as required by most of the Kubernetes resource names
+pub fn is_dns1123_label<S: AsRef<str>>(input: S) -> bool {
+  let value = input.as_ref();
+  value.len() <= DNS1123_LABEL_MAX_LENGTH && DNS1123_LABEL_REGEX.is_match(value)
+}
+
+/// Checks if the input is a valid DNS-1123 subdomain: DNS-1123 labels separated by '.', as required by the names of
+/// the secrets
+pub fn is_dns1123_subdomain<S: AsRef<str>>(input: S) -> bool {
+  let value = input.as_ref();
+  value.len() <= DNS1123_SUBDOMAIN_MAX_LENGTH && value.split('.').all(is_dns1123_label)
+}
+
+/// Checks if the input is a valid qualified name, as required by the keys of the labels and the taints: a name of up to
+/// 63 alphanumeric characters, '-', '_' or '.', optionally prefixed by a DNS-1123 subdomain and '/'
+pub fn is_qualified_name<S: AsRef<str>>(input: S) -> bool {
+  let (prefix, name) = match input.as_ref().split_once('/') {
+    Some((prefix, name)) => (Some(prefix), name),
+    None => (None, input.as_ref()),
+  };
+  prefix.iter().all(is_dns1123_subdomain) && is_qualified_name_part(name)
+}
+
+/// Checks if the input is a valid value of a label or a taint: empty, or a name of a qualified name
+pub fn is_label_value<S: AsRef<str>>(input: S) -> bool {
+  let value = input.as_ref();
+  value.is_empty() || is_qualified_name_part(value)
+}
+
+fn is_qualified_name_part(value: &str) -> bool {
+  value.len() <= QUALIFIED_NAME_MAX_LENGTH && QUALIFIED_NAME_REGEX.is_match(value)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::validators::dns::{is_dns1123_label, is_dns1123_subdomain, is_label_value, is_qualified_name};
+
+  const VALID_TEST_CASES: &[&str] = &[
+    "a",
+    "gpu-pool",
+    "pool-1",
+    "0pool",
+    "abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz0",
+  ];
+
+  const INVALID_TEST_CASES: &[&str] = &[
+    "",
+    "-pool",
+    "pool-",
+    "GPU",
+    "gpu_pool",
+    "gpu.pool",
+    "abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopqrstuvwxyz01",
+  ];
+
+  #[test]
+  fn check_valid_inputs() {
+    for value in VALID_TEST_CASES {
+      assert!(is_dns1123_label(value), "{} should be valid", value)
+    }
+  }
+
+  #[test]
+  fn check_invalid_inputs() {
+    for value in INVALID_TEST_CASES {
+      assert!(!is_dns1123_label(value), "{} should be invalid", value)
+    }
+  }
+
+  #[test]
+  fn check_subdomains() {
+    for value in ["db-credentials", "tls.example.com", "0pool"] {
+      assert!(is_dns1123_subdomain(value), "{} should be valid", value)
+    }
+    for value in ["", "db_credentials", ".tls", "tls..example", "Tls.example", &"a".repeat(254)] {
+      assert!(!is_dns1123_subdomain(value), "{} should be invalid", value)
+    }
+  }
+
+  #[test]
+  fn check_qualified_names_and_label_values() {
+    for value in ["team", "node.kubernetes.io/lifecycle", "example.com/GPU_type", "a.b-c_d"] {
+      assert!(is_qualified_name(value), "{} should be valid", value)
+    }
+    for value in [
+      "",
+      "foo bar",
+      "a/b/c",
+      "/team",
+      "Example.com/team",
+      "team-",
+      "example.com/",
+      &"a".repeat(64),
+    ] {
+      assert!(!is_qualified_name(value), "{} should be invalid", value)
+    }
+    for value in ["", "data", "spot_instances", "v1.2-rc"] {
+      assert!(is_label_value(value), "{} should be valid", value)
+    }
+    for value in ["a b", "-data", "data.", "team/data", &"a".repeat(64)] {
+      assert!(!is_label_value(value), "{} should be invalid", value)
+    }
+  }
+}
@@ -0,0 +1,3 @@
+pub mod dns;
+pub mod paths;
+pub mod secrets;
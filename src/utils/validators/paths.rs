@@ -0,0 +1,48 @@
+use std::path::{Component, Path};
+
+/// Checks if the input is a single file or folder name: no separator, and neither `.` nor `..`
+pub fn is_path_component<S: AsRef<str>>(input: S) -> bool {
+  let value = input.as_ref();
+  !matches!(value, "" | "." | "..") && !value.contains(['/', '\\', '\0'])
+}
+
+/// Checks if the input is a relative path staying inside its base folder: no root and no `..` component
+pub fn is_contained_path<S: AsRef<str>>(input: S) -> bool {
+  let value = input.as_ref();
+  !value.is_empty()
+    && !value.contains(['\\', '\0'])
+    && Path::new(value)
+      .components()
+      .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::validators::paths::{is_contained_path, is_path_component};
+
+  #[test]
+  fn check_the_path_components() {
+    for value in ["pool", "db-credentials", "sealed-secret-token.yaml", "..hidden"] {
+      assert!(is_path_component(value), "{} should be a path component", value);
+    }
+    for value in ["", ".", "..", "../pool", "pool/config", "pool\\config", "pool\0"] {
+      assert!(!is_path_component(value), "{} shouldn't be a path component", value);
+    }
+  }
+
+  #[test]
+  fn check_the_contained_paths() {
+    for value in ["nodegroup-pool.yaml", "clusters/staging/pool.yaml", "./clusters/pool.yaml"] {
+      assert!(is_contained_path(value), "{} should be contained", value);
+    }
+    for value in [
+      "",
+      "/etc/passwd",
+      "../pool.yaml",
+      "clusters/../../pool.yaml",
+      "clusters\\..\\pool.yaml",
+    ] {
+      assert!(!is_contained_path(value), "{} shouldn't be contained", value);
+    }
+  }
+}
@@ -0,0 +1,74 @@
+use openssl::asn1::Asn1Time;
+use openssl::pkey::PKey;
+use openssl::x509::X509;
+use regex::Regex;
+
+lazy_static! {
+  static ref SECRET_KEY_REGEX: Regex = Regex::new(r"^[-._a-zA-Z0-9]+$").unwrap();
+}
+
+/// Checks if the input is a valid key of the data of a secret: alphanumeric characters, '-', '_' or '.'
+pub fn is_secret_key<S: AsRef<str>>(input: S) -> bool {
+  SECRET_KEY_REGEX.is_match(input.as_ref())
+}
+
+/// Checks if the input starts with a PEM certificate that hasn't expired. The rest of the chain isn't checked.
+pub fn is_valid_pem_certificate<S: AsRef<str>>(input: S) -> bool {
+  match (X509::from_pem(input.as_ref().as_bytes()), Asn1Time::days_from_now(0)) {
+    (Ok(certificate), Ok(now)) => certificate.not_after() > now,
+    _ => false,
+  }
+}
+
+/// Checks if the input is a PEM private key (PKCS#1, PKCS#8 or SEC1)
+pub fn is_pem_private_key<S: AsRef<str>>(input: S) -> bool {
+  PKey::private_key_from_pem(input.as_ref().as_bytes()).is_ok()
+}
+
+/// Checks if the private key is the one of the first PEM certificate
+pub fn is_matching_key_pair<C: AsRef<str>, K: AsRef<str>>(certificate: C, key: K) -> bool {
+  let certificate = X509::from_pem(certificate.as_ref().as_bytes()).and_then(|certificate| certificate.public_key());
+  let key = PKey::private_key_from_pem(key.as_ref().as_bytes());
+  match (certificate, key) {
+    (Ok(certificate), Ok(key)) => certificate.public_eq(&key),
+    _ => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use openssl::asn1::Asn1Time;
+
+  use crate::infrastructure::sealed_secrets::client::tests::{controller_keys, signed_certificate};
+  use crate::utils::validators::secrets::{is_matching_key_pair, is_pem_private_key, is_secret_key, is_valid_pem_certificate};
+
+  #[test]
+  fn check_the_secret_keys() {
+    for value in ["password", "tls.crt", ".dockerconfigjson", "API_KEY-2"] {
+      assert!(is_secret_key(value), "{} should be a secret key", value);
+    }
+    for value in ["", "api key", "../key", "clé"] {
+      assert!(!is_secret_key(value), "{} shouldn't be a secret key", value);
+    }
+  }
+
+  #[test]
+  fn check_the_certificates_and_their_keys() {
+    let (key, certificate) = controller_keys();
+    let certificate = String::from_utf8(certificate).unwrap();
+    let key = String::from_utf8(key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+    let (other_key, _) = controller_keys();
+    let other_key = String::from_utf8(other_key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+
+    assert!(is_valid_pem_certificate(&certificate));
+    assert!(is_pem_private_key(&key));
+    assert!(is_matching_key_pair(&certificate, &key));
+    assert!(!is_matching_key_pair(&certificate, &other_key));
+    assert!(!is_pem_private_key(&certificate));
+    assert!(!is_valid_pem_certificate(&key));
+
+    let (expired_key, _) = controller_keys();
+    let expired = signed_certificate(&expired_key, &Asn1Time::from_unix(86400).unwrap());
+    assert!(!is_valid_pem_certificate(String::from_utf8(expired).unwrap()));
+  }
+}